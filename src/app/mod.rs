@@ -0,0 +1,166 @@
+#[cfg(target_os = "linux")]
+use image::RgbImage;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+
+use capture;
+use context::Surface;
+use gl_gen::gl;
+#[cfg(target_os = "linux")]
+use headless::HeadlessContext;
+use input::{Controllers, InputState};
+use window::Window;
+
+mod timing;
+pub use self::timing::{frame_period, FixedStep, FrameStats, Timestep, Timing};
+use self::timing::{Clock, FrameLimiter};
+
+/// What the main loop should do after an event was handled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Exit,
+}
+
+/// Hooks driven by `run`. Only `init` and `render` are required.
+///
+/// `init`, `update` and `render` only see a `Surface` so the same app can also
+/// be rendered offscreen by `render_headless`.
+pub trait App: Sized {
+    /// Builds the application once the surface and its GL context exist, so
+    /// GL objects can be created here.
+    fn init(surface: &dyn Surface) -> Self;
+
+    /// Called once per frame before `update` with the keyboard and mouse
+    /// state, for continuous controls such as held movement keys or
+    /// mouse-look. Only windowed apps receive input.
+    fn handle_input(&mut self, _window: &Window, _input: &InputState, _dt: f32) {}
+
+    /// Advances the simulation by `dt` seconds. With `Timestep::Fixed` this is
+    /// called zero or more times per frame, always with the same `dt`.
+    fn update(&mut self, _surface: &dyn Surface, _dt: f32) {}
+
+    /// Called before `render` with a fixed timestep: how far, 0.0 to 1.0, the
+    /// frame is between the last update and the next. Apps that keep their
+    /// previous state can render a blend of the two for smooth motion.
+    fn interpolate(&mut self, _alpha: f32) {}
+
+    /// Draws one frame. The buffers are swapped afterwards.
+    fn render(&mut self, surface: &dyn Surface);
+
+    /// Called after each windowed frame with the timing of the last frames.
+    fn frame_stats(&mut self, _stats: &FrameStats) {}
+
+    /// Called for every SDL event; only windowed apps receive events. By
+    /// default closing the window or pressing Escape exits.
+    fn handle_event(&mut self, _window: &Window, event: &Event) -> Flow {
+        match *event {
+            Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => Flow::Exit,
+            _ => Flow::Continue,
+        }
+    }
+}
+
+/// Creates the app and runs its event/update/render loop until it asks to exit.
+/// The GL viewport follows the window size and `capture::SCREENSHOT_KEY`
+/// saves a screenshot of the next frame. Game controllers are opened as they
+/// are plugged in and feed the same `InputState` as the keyboard and mouse.
+///
+/// Each frame handles the pending events, then calls `handle_input`,
+/// `update` and `render`. Steps are variable; see `run_with` for other timing.
+pub fn run<A: App>(window: Window) {
+    run_with::<A>(window, Timing::default());
+}
+
+/// `run` with the timestep, frame cap and statistics of `timing`. Time is
+/// measured with SDL's high-resolution performance counter.
+pub fn run_with<A: App>(window: Window, timing: Timing) {
+    let mut app = A::init(&window);
+    let mut event_pump = window.event_pump();
+    let mut clock = Clock::new(window.timer());
+    let mut limiter = FrameLimiter::new(&window, timing.get_frame_cap());
+    let mut fixed = match timing.get_timestep() {
+        Timestep::Fixed(step) => Some(FixedStep::new(step)),
+        Timestep::Variable => None,
+    };
+    let mut stats = FrameStats::new(timing.get_stats_frames());
+    let mut last_print = 0.0;
+    let mut screenshot = capture::ScreenshotHotkey::new();
+    let mut input = InputState::new();
+    // Without controller support the app still runs on keyboard and mouse
+    let mut controllers = Controllers::new(window.sdl())
+        .map_err(|err| eprintln!("{}", err))
+        .ok();
+
+    'running: loop {
+        input.begin_frame();
+        for event in event_pump.poll_iter() {
+            if let Event::Window { win_event: WindowEvent::SizeChanged(..), .. } = event {
+                let (width, height) = window.drawable_size();
+                unsafe {
+                    gl::Viewport(0, 0, width as i32, height as i32);
+                }
+            }
+
+            if screenshot.handle_event(&event) {
+                continue;
+            }
+
+            if let Some(ref mut controllers) = controllers {
+                if let Err(err) = controllers.handle_event(&event) {
+                    eprintln!("{}", err);
+                }
+            }
+            input.handle_event(&event);
+            if app.handle_event(&window, &event) == Flow::Exit {
+                break 'running;
+            }
+        }
+        input.set_held(event_pump.keyboard_state().pressed_scancodes(),
+                       event_pump.mouse_state().pressed_mouse_buttons());
+
+        let frame_time = clock.tick();
+        stats.record(frame_time);
+        let dt = frame_time.min(timing.get_max_frame_time());
+
+        app.handle_input(&window, &input, dt);
+        match fixed {
+            Some(ref mut fixed) => {
+                for _ in 0..fixed.advance(dt) {
+                    app.update(&window, fixed.step());
+                }
+                app.interpolate(fixed.alpha());
+            },
+            None => app.update(&window, dt),
+        }
+        app.render(&window);
+        screenshot.capture_if_requested(&window);
+        window.swap();
+        limiter.wait(&clock);
+
+        app.frame_stats(&stats);
+        if timing.get_print_stats() && clock.now() - last_print >= 1.0 {
+            last_print = clock.now();
+            println!("{:.1} fps, {:.2} ms/frame (min {:.2}, max {:.2})",
+                     stats.fps(),
+                     stats.average_frame_time() * 1000.0,
+                     stats.min_frame_time() * 1000.0,
+                     stats.max_frame_time() * 1000.0);
+        }
+    }
+}
+
+/// Initializes the app on `context`, advances it `frames` times by `dt` seconds
+/// and returns the image of the frame rendered afterwards. Runs without
+/// events, so the result only depends on the simulated time.
+#[cfg(target_os = "linux")]
+pub fn render_headless<A: App>(context: &HeadlessContext, frames: u32, dt: f32) -> RgbImage {
+    let mut app = A::init(context);
+    for _ in 0..frames {
+        app.update(context, dt);
+    }
+
+    context.bind();
+    app.render(context);
+    context.screenshot()
+}
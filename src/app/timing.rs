@@ -0,0 +1,345 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+use sdl2::TimerSubsystem;
+
+use window::Window;
+
+/// How `run_with` advances the simulation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Timestep {
+    /// One `update` per frame by the time the frame took.
+    Variable,
+    /// `update` in steps of this many seconds, as many as fit in the elapsed
+    /// time, with `App::interpolate` told how far rendering is into the next
+    /// step. Deterministic, so physics behaves the same at any frame rate.
+    Fixed(f32),
+}
+
+/// Settings of the main loop, chained onto `new`:
+///
+/// ```no_run
+/// # extern crate opengl_sdl2_demo;
+/// # use opengl_sdl2_demo::app::{Timestep, Timing};
+/// # fn main() {
+/// let timing = Timing::new()
+///     .timestep(Timestep::Fixed(1.0 / 120.0))
+///     .frame_cap(Some(144.0))
+///     .print_stats(true);
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Timing {
+    timestep: Timestep,
+    frame_cap: Option<f32>,
+    max_frame_time: f32,
+    stats_frames: usize,
+    print_stats: bool,
+}
+
+impl Default for Timing {
+    /// Variable steps, no frame cap beyond vsync.
+    fn default() -> Timing {
+        Timing {
+            timestep: Timestep::Variable,
+            frame_cap: None,
+            max_frame_time: 0.25,
+            stats_frames: 120,
+            print_stats: false,
+        }
+    }
+}
+
+impl Timing {
+    pub fn new() -> Timing {
+        Timing::default()
+    }
+
+    pub fn timestep(mut self, timestep: Timestep) -> Timing {
+        self.timestep = timestep;
+        self
+    }
+
+    /// Limits the frame rate to `fps`. Skipped when vsync already holds the
+    /// frame rate at or below the cap, so the two never wait on each other.
+    pub fn frame_cap(mut self, fps: Option<f32>) -> Timing {
+        self.frame_cap = fps;
+        self
+    }
+
+    /// Longer frames, e.g. after the window was dragged or a breakpoint hit,
+    /// only advance the simulation by this many seconds.
+    pub fn max_frame_time(mut self, seconds: f32) -> Timing {
+        self.max_frame_time = seconds;
+        self
+    }
+
+    /// How many of the last frames `FrameStats` averages over.
+    pub fn stats_frames(mut self, frames: usize) -> Timing {
+        self.stats_frames = frames.max(1);
+        self
+    }
+
+    /// Prints the frame statistics about once a second.
+    pub fn print_stats(mut self, print_stats: bool) -> Timing {
+        self.print_stats = print_stats;
+        self
+    }
+
+    pub fn get_timestep(&self) -> Timestep {
+        self.timestep
+    }
+
+    pub fn get_frame_cap(&self) -> Option<f32> {
+        self.frame_cap
+    }
+
+    pub fn get_max_frame_time(&self) -> f32 {
+        self.max_frame_time
+    }
+
+    pub fn get_stats_frames(&self) -> usize {
+        self.stats_frames
+    }
+
+    pub fn get_print_stats(&self) -> bool {
+        self.print_stats
+    }
+}
+
+/// Splits elapsed time into fixed steps, carrying the remainder over to the
+/// next frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FixedStep {
+    step: f32,
+    accumulator: f32,
+}
+
+impl FixedStep {
+    pub fn new(step: f32) -> FixedStep {
+        assert!(step > 0.0, "fixed timestep must be positive, got {}", step);
+        FixedStep { step, accumulator: 0.0 }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Adds `dt` seconds and returns how many steps to simulate.
+    pub fn advance(&mut self, dt: f32) -> u32 {
+        self.accumulator += dt;
+        let steps = (self.accumulator / self.step).floor();
+        self.accumulator -= steps * self.step;
+        steps as u32
+    }
+
+    /// How far, 0.0 to 1.0, the time left over is into the next step; blend
+    /// the previous and current state by it when rendering.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+}
+
+/// Frame times of the last frames, in seconds.
+#[derive(Clone, Debug)]
+pub struct FrameStats {
+    frame_times: VecDeque<f32>,
+    capacity: usize,
+    total: f32,
+    frames: u64,
+}
+
+impl FrameStats {
+    /// Keeps the last `capacity` frames.
+    pub fn new(capacity: usize) -> FrameStats {
+        let capacity = capacity.max(1);
+        FrameStats {
+            frame_times: VecDeque::with_capacity(capacity),
+            capacity,
+            total: 0.0,
+            frames: 0,
+        }
+    }
+
+    pub fn record(&mut self, frame_time: f32) {
+        if self.frame_times.len() == self.capacity {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+        // Summed again rather than kept running so rounding errors don't pile up
+        self.total = self.frame_times.iter().sum();
+        self.frames += 1;
+    }
+
+    /// Frames recorded since the start, not only the last ones.
+    pub fn frame_count(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn average_frame_time(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            0.0
+        } else {
+            self.total / self.frame_times.len() as f32
+        }
+    }
+
+    pub fn fps(&self) -> f32 {
+        if self.total > 0.0 {
+            self.frame_times.len() as f32 / self.total
+        } else {
+            0.0
+        }
+    }
+
+    pub fn min_frame_time(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            0.0
+        } else {
+            self.frame_times.iter().cloned().fold(f32::INFINITY, f32::min)
+        }
+    }
+
+    pub fn max_frame_time(&self) -> f32 {
+        self.frame_times.iter().cloned().fold(0.0, f32::max)
+    }
+}
+
+/// Seconds from SDL's high-resolution performance counter.
+pub(super) struct Clock {
+    timer: TimerSubsystem,
+    frequency: f64,
+    start: u64,
+    last: u64,
+}
+
+impl Clock {
+    pub(super) fn new(timer: TimerSubsystem) -> Clock {
+        let frequency = timer.performance_frequency() as f64;
+        let start = timer.performance_counter();
+        Clock { timer, frequency, start, last: start }
+    }
+
+    /// Seconds since the clock was created.
+    pub(super) fn now(&self) -> f64 {
+        self.timer.performance_counter().wrapping_sub(self.start) as f64 / self.frequency
+    }
+
+    /// Seconds since the previous call.
+    pub(super) fn tick(&mut self) -> f32 {
+        let counter = self.timer.performance_counter();
+        let elapsed = counter.wrapping_sub(self.last) as f64 / self.frequency;
+        self.last = counter;
+        elapsed as f32
+    }
+}
+
+/// The seconds between frames the cap asks for, or `None` when there is no
+/// cap or vsync at `vsync_rate` Hz already keeps to it.
+pub fn frame_period(frame_cap: Option<f32>, vsync_rate: Option<i32>) -> Option<f64> {
+    let cap = frame_cap.filter(|&cap| cap > 0.0)?;
+    match vsync_rate {
+        Some(rate) if rate > 0 && rate as f32 <= cap => None,
+        _ => Some(1.0 / cap as f64),
+    }
+}
+
+/// Sleeps off the rest of each frame to hold a frame cap. Frames are paced
+/// against deadlines rather than by sleeping a whole period, so the frame
+/// rate doesn't drift below the cap.
+pub(super) struct FrameLimiter {
+    period: Option<f64>,
+    deadline: f64,
+}
+
+impl FrameLimiter {
+    pub(super) fn new(window: &Window, frame_cap: Option<f32>) -> FrameLimiter {
+        let vsync_rate = if window.video().gl_get_swap_interval() != 0 {
+            window.window().display_mode().ok().map(|mode| mode.refresh_rate)
+        } else {
+            None
+        };
+
+        FrameLimiter {
+            period: frame_period(frame_cap, vsync_rate),
+            deadline: 0.0,
+        }
+    }
+
+    pub(super) fn wait(&mut self, clock: &Clock) {
+        let period = match self.period {
+            Some(period) => period,
+            None => return,
+        };
+
+        let now = clock.now();
+        self.deadline += period;
+        if self.deadline < now {
+            // Too slow to keep up; start pacing again from here
+            self.deadline = now;
+            return;
+        }
+
+        loop {
+            let remaining = self.deadline - clock.now();
+            if remaining <= 0.0 {
+                break;
+            }
+            // Sleeps can overshoot by about a millisecond, so the end is spun
+            if remaining > 0.002 {
+                thread::sleep(Duration::from_secs_f64(remaining - 0.001));
+            } else {
+                thread::yield_now();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    #[test]
+    fn fixed_steps_carry_the_remainder() {
+        let mut fixed = FixedStep::new(0.01);
+
+        assert_eq!(fixed.advance(0.025), 2);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-3);
+
+        assert_eq!(fixed.advance(0.004), 0);
+        assert_eq!(fixed.advance(0.002), 1);
+        assert!((fixed.alpha() - 0.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stats_roll_over_the_last_frames() {
+        let mut stats = FrameStats::new(4);
+        assert_eq!(stats.fps(), 0.0);
+
+        for &time in &[1.0, 0.02, 0.01, 0.02, 0.01] {
+            stats.record(time);
+        }
+
+        // The one-second frame has dropped out
+        assert!((stats.average_frame_time() - 0.015).abs() < EPSILON);
+        assert!((stats.fps() - 1.0 / 0.015).abs() < 0.01);
+        assert_eq!(stats.min_frame_time(), 0.01);
+        assert_eq!(stats.max_frame_time(), 0.02);
+        assert_eq!(stats.frame_count(), 5);
+    }
+
+    #[test]
+    fn frame_cap_defers_to_vsync() {
+        assert_eq!(frame_period(None, None), None);
+        assert_eq!(frame_period(Some(100.0), None), Some(0.01));
+        // 60 Hz vsync already stays under 144 fps
+        assert_eq!(frame_period(Some(144.0), Some(60)), None);
+        // 144 Hz vsync does not stay under 60 fps
+        assert!((frame_period(Some(60.0), Some(144)).unwrap() - 1.0 / 60.0).abs() < 1e-9);
+        // Unknown refresh rates are reported as 0
+        assert!(frame_period(Some(60.0), Some(0)).is_some());
+    }
+}
@@ -1,5 +1,13 @@
-use cgmath::{Angle, InnerSpace, Matrix4, Rad, Point3, Vector3};
+use cgmath::{Angle, Deg, InnerSpace, Matrix3, Matrix4, One, Point3, Quaternion, Rotation3, Vector3};
 use cgmath::EuclideanSpace;
+use sdl2::event::{Event, WindowEvent};
+
+mod mouse_look;
+mod orbit;
+mod projection;
+pub use self::mouse_look::MouseLook;
+pub use self::orbit::OrbitCamera;
+pub use self::projection::Projection;
 
 const DEFAULT_YAW: f32 = -90.0;
 const DEFAULT_PITCH: f32 = 0.0;
@@ -10,14 +18,64 @@ const DEFAULT_ZOOM: f32 = 45.0;
 const ZOOM_LOW: f32 = 1.0;
 const ZOOM_HIGH: f32 = 45.0;
 
+/// Keeps the FPS view direction off the world up axis, where yaw is undefined.
+const PITCH_LIMIT: f32 = 89.0;
+
+/// Until the camera is told the size of the viewport.
+const DEFAULT_ASPECT_RATIO: f32 = 1.0;
+
+/// What rendering needs from a camera, so an app can switch between a fly
+/// `Camera` and an `OrbitCamera` at runtime.
+pub trait ViewCamera {
+    fn get_view(&self) -> Matrix4<f32>;
+    /// Vertical field of view in degrees.
+    fn get_zoom(&self) -> f32;
+    fn get_position(&self) -> Vector3<f32>;
+    fn get_direction(&self) -> Vector3<f32>;
+    fn projection(&self) -> Matrix4<f32>;
+
+    fn view_projection(&self) -> Matrix4<f32> {
+        self.projection() * self.get_view()
+    }
+}
+
+/// The aspect ratio of a `SizeChanged` window event.
+fn resized_aspect_ratio(event: &Event) -> Option<f32> {
+    match *event {
+        Event::Window { win_event: WindowEvent::SizeChanged(width, height), .. } => {
+            Some(width as f32 / height.max(1) as f32)
+        },
+        _ => None,
+    }
+}
+
 pub enum CameraMovement {
     FORWARD,
     BACKWARD,
     LEFT,
     RIGHT,
+    /// Along the camera's own up axis, for six-degree-of-freedom flight.
+    UP,
+    DOWN,
 }
 
+/// A slerp from one orientation to another, advanced by `Camera::update`.
 #[derive(Copy, Clone, Debug)]
+struct Turn {
+    from: Quaternion<f32>,
+    to: Quaternion<f32>,
+    elapsed: f32,
+    duration: f32,
+}
+
+/// A free camera. Its orientation is a quaternion, turned either with the
+/// yaw/pitch API for FPS controls (`move_rotation`), which keeps the horizon
+/// level and stops short of looking straight up or down, or around its own
+/// axes (`rotate_local`) for six-degree-of-freedom flight with roll.
+///
+/// Angles are in degrees. A yaw of -90° looks down `-Z`, and yaw and pitch
+/// always describe the current view direction.
+#[derive(Clone, Debug)]
 pub struct Camera {
     // Camera Vectors
     position: Vector3<f32>,
@@ -26,6 +84,10 @@ pub struct Camera {
     world_up: Vector3<f32>,
     right: Vector3<f32>,
 
+    /// Maps the camera's axes (right `+X`, up `+Y`, forward `-Z`) to world space.
+    orientation: Quaternion<f32>,
+    turn: Option<Turn>,
+
     // Euler angles
     yaw: f32,
     pitch: f32,
@@ -34,39 +96,131 @@ pub struct Camera {
     speed: f32,
     sensitivity: f32,
     zoom: f32,
+    projection: Projection,
+    aspect_ratio: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera::new(Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(0.0, 1.0, 0.0),
+                    DEFAULT_PITCH, DEFAULT_YAW)
+    }
 }
 
 impl Camera {
+    /// `pitch` is clamped to `PITCH_LIMIT`.
     pub fn new(position: Vector3<f32>, up: Vector3<f32>, pitch: f32, yaw: f32) -> Camera {
-        let new_cam = Camera {
+        let mut new_cam = Camera {
             position,
             world_up: up,
             up: Vector3::new(0.0, 0.0, 0.0),
             direction: Vector3::new(0.0, 0.0, 0.0),
             right: Vector3::new(0.0, 0.0, 0.0),
-            yaw: yaw,
-            pitch: pitch,
+            orientation: Quaternion::one(),
+            turn: None,
+            yaw,
+            pitch,
             speed: DEFAULT_SPEED,
             sensitivity: DEFAULT_SENSITIVITY,
             zoom: DEFAULT_ZOOM,
+            projection: Projection::default(),
+            aspect_ratio: DEFAULT_ASPECT_RATIO,
         };
 
-        new_cam.recalculate_vectors();
+        new_cam.level();
 
         new_cam
     }
 
-    pub fn get_view(self) -> Matrix4<f32> {
+    /// A camera at `position` looking along `direction`, e.g. one taken from
+    /// a scene file.
+    pub fn with_direction(position: Vector3<f32>, direction: Vector3<f32>, up: Vector3<f32>) -> Camera {
+        let direction = direction.normalize();
+        let yaw = direction.z.atan2(direction.x).to_degrees();
+        let pitch = direction.y.asin().to_degrees();
+
+        Camera::new(position, up, pitch, yaw)
+    }
+
+    pub fn get_view(&self) -> Matrix4<f32> {
         Matrix4::look_at(Point3::from_vec(self.position), 
                          Point3::from_vec(self.position + self.direction), 
                          self.up)
     }
 
-    pub fn get_zoom(self) -> f32 {
+    pub fn get_zoom(&self) -> f32 {
         self.zoom
     }
 
-    pub fn move_position(mut self, move_type: CameraMovement, delta: f32) {
+    pub fn get_projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    pub fn get_aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    /// Sets the width / height ratio of the viewport, e.g. from
+    /// `Surface::aspect_ratio` when the app starts.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    /// Follows the window size; pass every event to it.
+    pub fn track_resize(&mut self, event: &Event) {
+        if let Some(aspect_ratio) = resized_aspect_ratio(event) {
+            self.aspect_ratio = aspect_ratio;
+        }
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        self.projection.matrix(self.zoom, self.aspect_ratio)
+    }
+
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection() * self.get_view()
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    pub fn get_direction(&self) -> Vector3<f32> {
+        self.direction
+    }
+
+    pub fn get_right(&self) -> Vector3<f32> {
+        self.right
+    }
+
+    pub fn get_up(&self) -> Vector3<f32> {
+        self.up
+    }
+
+    pub fn get_yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    pub fn get_pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    pub fn get_orientation(&self) -> Quaternion<f32> {
+        self.orientation
+    }
+
+    /// Replaces the orientation, including any roll, and stops a turn.
+    pub fn set_orientation(&mut self, orientation: Quaternion<f32>) {
+        self.turn = None;
+        self.orient(orientation);
+    }
+
+    pub fn move_position(&mut self, move_type: CameraMovement, delta: f32) {
         let velocity = self.speed * delta;
 
         match move_type {
@@ -74,26 +228,357 @@ impl Camera {
             CameraMovement::BACKWARD => self.position -= self.direction * velocity,
             CameraMovement::LEFT => self.position -= self.right * velocity,
             CameraMovement::RIGHT => self.position += self.right * velocity,
+            CameraMovement::UP => self.position += self.up * velocity,
+            CameraMovement::DOWN => self.position -= self.up * velocity,
         }
     }
 
-    pub fn move_rotation(mut self, yaw: f32, pitch: f32) {
-        self.yaw += self.sensitivity * yaw;
-        self.pitch += self.sensitivity * pitch;
+    /// FPS-style look, scaled by the sensitivity. See `look`.
+    pub fn move_rotation(&mut self, yaw: f32, pitch: f32) {
+        let sensitivity = self.sensitivity;
+        self.look(sensitivity * yaw, sensitivity * pitch);
+    }
+
+    /// FPS-style look by the given degrees: yaw turns around the world up
+    /// axis, pitch is clamped to `PITCH_LIMIT` and any roll is levelled.
+    pub fn look(&mut self, yaw: f32, pitch: f32) {
+        self.yaw += yaw;
+        self.pitch += pitch;
+
+        self.turn = None;
+        self.level();
+    }
+
+    /// Turns around the camera's own axes by the given degrees, without
+    /// limits: positive pitch looks up, positive yaw turns right and positive
+    /// roll banks to the right. Not scaled by the sensitivity.
+    pub fn rotate_local(&mut self, pitch: f32, yaw: f32, roll: f32) {
+        let rotation = Quaternion::from_axis_angle(Vector3::unit_y(), Deg(-yaw))
+            * Quaternion::from_axis_angle(Vector3::unit_x(), Deg(pitch))
+            * Quaternion::from_axis_angle(-Vector3::unit_z(), Deg(roll));
+
+        self.turn = None;
+        self.orient(self.orientation * rotation);
+    }
+
+    /// Looks at `target` with the horizon level.
+    pub fn look_at(&mut self, target: Vector3<f32>) {
+        self.set_orientation(orientation_towards(target - self.position, self.world_up, self.up));
+    }
+
+    /// Starts turning towards `orientation`, reached after `duration` seconds
+    /// of `update`. Other rotations cancel the turn.
+    pub fn turn_to(&mut self, orientation: Quaternion<f32>, duration: f32) {
+        if duration <= 0.0 {
+            self.set_orientation(orientation);
+            return;
+        }
+
+        // Of the two quaternions for the same rotation, slerp towards the
+        // nearer one so the camera takes the short way round
+        let to = if self.orientation.dot(orientation) < 0.0 { -orientation } else { orientation };
+        self.turn = Some(Turn { from: self.orientation, to, elapsed: 0.0, duration });
+    }
 
-        self.recalculate_vectors();
+    /// Like `look_at`, but turning over `duration` seconds.
+    pub fn turn_towards(&mut self, target: Vector3<f32>, duration: f32) {
+        let orientation = orientation_towards(target - self.position, self.world_up, self.up);
+        self.turn_to(orientation, duration);
     }
 
-    pub fn move_zoom(mut self, zoom: f32) {
-        self.zoom = f32::min(zoom, ZOOM_HIGH);
-        self.zoom = f32::max(zoom, ZOOM_LOW);
+    pub fn is_turning(&self) -> bool {
+        self.turn.is_some()
+    }
+
+    /// Advances a turn started by `turn_to` by `dt` seconds.
+    pub fn update(&mut self, dt: f32) {
+        if let Some(mut turn) = self.turn {
+            turn.elapsed += dt;
+            let t = (turn.elapsed / turn.duration).min(1.0);
+            self.orient(turn.from.slerp(turn.to, t).normalize());
+            self.turn = if t < 1.0 { Some(turn) } else { None };
+        }
+    }
+
+    /// Sets the field of view to `zoom` degrees, clamped to
+    /// `ZOOM_LOW..=ZOOM_HIGH`.
+    pub fn move_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(ZOOM_LOW, ZOOM_HIGH);
+    }
+
+    /// Narrows the field of view by `amount` degrees (negative values widen
+    /// it), e.g. by mouse wheel steps. Clamped like `move_zoom`.
+    pub fn zoom_by(&mut self, amount: f32) {
+        let zoom = self.zoom - amount;
+        self.move_zoom(zoom);
+    }
+
+    /// Rebuilds the orientation from yaw and pitch, without roll.
+    fn level(&mut self) {
+        self.pitch = self.pitch.clamp(-PITCH_LIMIT, PITCH_LIMIT);
+
+        let (yaw, pitch) = (Deg(self.yaw), Deg(self.pitch));
+        let direction = Vector3::new(yaw.cos() * pitch.cos(),
+                                     pitch.sin(),
+                                     yaw.sin() * pitch.cos());
+        let orientation = orientation_towards(direction, self.world_up, self.up);
+        self.orient(orientation);
+    }
+
+    /// Sets the orientation and everything derived from it.
+    fn orient(&mut self, orientation: Quaternion<f32>) {
+        self.orientation = orientation.normalize();
+        self.direction = (self.orientation * -Vector3::unit_z()).normalize();
+        self.right = (self.orientation * Vector3::unit_x()).normalize();
+        self.up = (self.orientation * Vector3::unit_y()).normalize();
+
+        self.yaw = self.direction.z.atan2(self.direction.x).to_degrees();
+        self.pitch = self.direction.y.clamp(-1.0, 1.0).asin().to_degrees();
+    }
+}
+
+/// The orientation looking along `direction` with `up` pointing up as far as
+/// possible. `fallback_up` is used when the two are parallel.
+fn orientation_towards(direction: Vector3<f32>, up: Vector3<f32>, fallback_up: Vector3<f32>) -> Quaternion<f32> {
+    let forward = direction.normalize();
+    let mut right = forward.cross(up);
+    if right.magnitude2() < 1e-12 {
+        right = forward.cross(fallback_up);
+    }
+    let right = right.normalize();
+    let up = right.cross(forward);
+
+    Quaternion::from(Matrix3::from_cols(right, up, -forward))
+}
+
+impl ViewCamera for Camera {
+    fn get_view(&self) -> Matrix4<f32> {
+        Camera::get_view(self)
     }
 
-    fn recalculate_vectors(mut self) {
-        self.direction = Vector3::new(Rad(self.yaw).cos() * Rad(self.pitch).cos(), 
-                                     Rad(self.pitch).sin(), 
-                                     Rad(self.yaw).sin() * Rad(self.pitch).cos()).normalize();
-        self.right = self.direction.cross(self.world_up).normalize();
-        self.up    = self.right.cross(self.direction).normalize();
+    fn get_zoom(&self) -> f32 {
+        Camera::get_zoom(self)
+    }
+
+    fn get_position(&self) -> Vector3<f32> {
+        Camera::get_position(self)
+    }
+
+    fn get_direction(&self) -> Vector3<f32> {
+        Camera::get_direction(self)
+    }
+
+    fn projection(&self) -> Matrix4<f32> {
+        Camera::projection(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn camera() -> Camera {
+        Camera::new(Vector3::new(0.0, 0.0, 3.0), Vector3::new(0.0, 1.0, 0.0), 0.0, -90.0)
+    }
+
+    fn assert_vec_eq(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).magnitude() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn new_computes_orthonormal_basis() {
+        let cam = camera();
+
+        assert!((cam.get_direction().magnitude() - 1.0).abs() < EPSILON);
+        assert!((cam.get_right().magnitude() - 1.0).abs() < EPSILON);
+        assert!((cam.get_up().magnitude() - 1.0).abs() < EPSILON);
+        assert!(cam.get_direction().dot(cam.get_right()).abs() < EPSILON);
+        assert!(cam.get_direction().dot(cam.get_up()).abs() < EPSILON);
+    }
+
+    #[test]
+    fn move_rotation_scales_by_sensitivity() {
+        let mut cam = camera();
+
+        cam.move_rotation(10.0, -20.0);
+
+        assert!((cam.get_yaw() - (-90.0 + 10.0 * DEFAULT_SENSITIVITY)).abs() < EPSILON);
+        assert!((cam.get_pitch() - (-20.0 * DEFAULT_SENSITIVITY)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn move_rotation_updates_direction() {
+        let mut cam = camera();
+        let before = cam.get_direction();
+
+        cam.move_rotation(100.0, 0.0);
+
+        assert!((cam.get_direction() - before).magnitude() > EPSILON);
+    }
+
+    #[test]
+    fn move_position_follows_direction_and_right() {
+        let mut cam = camera();
+        let start = cam.get_position();
+        let step = DEFAULT_SPEED * 0.5;
+
+        cam.move_position(CameraMovement::FORWARD, 0.5);
+        assert_vec_eq(cam.get_position(), start + cam.get_direction() * step);
+
+        cam.move_position(CameraMovement::BACKWARD, 0.5);
+        assert_vec_eq(cam.get_position(), start);
+
+        cam.move_position(CameraMovement::RIGHT, 0.5);
+        assert_vec_eq(cam.get_position(), start + cam.get_right() * step);
+
+        cam.move_position(CameraMovement::LEFT, 0.5);
+        assert_vec_eq(cam.get_position(), start);
+    }
+
+    #[test]
+    fn move_zoom_sets_the_field_of_view() {
+        let mut cam = camera();
+
+        cam.move_zoom(30.0);
+
+        assert_eq!(cam.get_zoom(), 30.0);
+    }
+
+    #[test]
+    fn move_zoom_clamps_to_range() {
+        let mut cam = camera();
+
+        cam.move_zoom(1000.0);
+        assert_eq!(cam.get_zoom(), ZOOM_HIGH);
+
+        cam.move_zoom(-1000.0);
+        assert_eq!(cam.get_zoom(), ZOOM_LOW);
+    }
+
+    #[test]
+    fn zoom_by_is_relative_and_clamped() {
+        let mut cam = camera();
+
+        cam.zoom_by(5.0);
+        assert_eq!(cam.get_zoom(), DEFAULT_ZOOM - 5.0);
+
+        cam.zoom_by(1000.0);
+        assert_eq!(cam.get_zoom(), ZOOM_LOW);
+    }
+
+    #[test]
+    fn yaw_and_pitch_are_degrees() {
+        assert_vec_eq(camera().get_direction(), Vector3::new(0.0, 0.0, -1.0));
+
+        let cam = Camera::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), 30.0, 0.0);
+        assert_vec_eq(cam.get_direction(), Vector3::new(3.0f32.sqrt() / 2.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn pitch_stops_short_of_the_poles() {
+        let mut cam = camera();
+
+        cam.move_rotation(0.0, 10000.0);
+
+        assert!((cam.get_pitch() - PITCH_LIMIT).abs() < 1e-3);
+        assert!(cam.get_up().y > 0.0);
+        assert!(cam.get_direction().z < 0.0);
+    }
+
+    #[test]
+    fn with_direction_round_trips() {
+        let direction = Vector3::new(1.0, -2.0, 3.0).normalize();
+        let cam = Camera::with_direction(Vector3::new(0.0, 0.0, 0.0), direction, Vector3::new(0.0, 1.0, 0.0));
+
+        assert_vec_eq(cam.get_direction(), direction);
+    }
+
+    #[test]
+    fn roll_banks_around_the_view_direction() {
+        let mut cam = camera();
+
+        cam.rotate_local(0.0, 0.0, 90.0);
+
+        assert_vec_eq(cam.get_direction(), Vector3::new(0.0, 0.0, -1.0));
+        assert_vec_eq(cam.get_up(), Vector3::new(1.0, 0.0, 0.0));
+        assert_vec_eq(cam.get_right(), Vector3::new(0.0, -1.0, 0.0));
+
+        // FPS look levels the horizon again
+        cam.move_rotation(0.0, 0.0);
+        assert_vec_eq(cam.get_up(), Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn local_rotations_pass_over_the_pole() {
+        let mut cam = camera();
+
+        cam.rotate_local(90.0, 0.0, 0.0);
+        cam.rotate_local(90.0, 0.0, 0.0);
+
+        assert_vec_eq(cam.get_direction(), Vector3::new(0.0, 0.0, 1.0));
+        assert_vec_eq(cam.get_up(), Vector3::new(0.0, -1.0, 0.0));
+
+        cam.rotate_local(0.0, 90.0, 0.0);
+        assert_vec_eq(cam.get_direction(), Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn look_at_faces_the_target() {
+        let mut cam = camera();
+
+        cam.look_at(Vector3::new(3.0, 0.0, 3.0));
+
+        assert_vec_eq(cam.get_direction(), Vector3::new(1.0, 0.0, 0.0));
+        assert_vec_eq(cam.get_up(), Vector3::new(0.0, 1.0, 0.0));
+        assert!((cam.get_yaw() - 0.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn turns_are_slerped_over_time() {
+        let mut cam = camera();
+
+        cam.turn_towards(Vector3::new(3.0, 0.0, 3.0), 1.0);
+        assert!(cam.is_turning());
+
+        cam.update(0.5);
+        let half = (0.5f32).sqrt();
+        assert_vec_eq(cam.get_direction(), Vector3::new(half, 0.0, -half));
+
+        cam.update(0.6);
+        assert_vec_eq(cam.get_direction(), Vector3::new(1.0, 0.0, 0.0));
+        assert!(!cam.is_turning());
+    }
+
+    #[test]
+    fn move_position_follows_up_when_rolled() {
+        let mut cam = camera();
+        cam.rotate_local(0.0, 0.0, 90.0);
+        let start = cam.get_position();
+
+        cam.move_position(CameraMovement::UP, 1.0);
+
+        assert_vec_eq(cam.get_position(), start + Vector3::new(DEFAULT_SPEED, 0.0, 0.0));
+    }
+
+    #[test]
+    fn track_resize_follows_size_changes() {
+        let mut cam = camera();
+        let resize = Event::Window { timestamp: 0, window_id: 1, win_event: WindowEvent::SizeChanged(800, 400) };
+
+        cam.track_resize(&resize);
+
+        assert_eq!(cam.get_aspect_ratio(), 2.0);
+        assert_eq!(cam.projection(), Projection::default().matrix(DEFAULT_ZOOM, 2.0));
+        assert_eq!(cam.view_projection(), cam.projection() * cam.get_view());
+    }
+
+    #[test]
+    fn get_view_places_camera_at_origin() {
+        let cam = camera();
+        let eye = cam.get_view() * cam.get_position().extend(1.0);
+
+        assert_vec_eq(eye.truncate(), Vector3::new(0.0, 0.0, 0.0));
     }
 }
@@ -0,0 +1,153 @@
+use super::{Camera, DEFAULT_SENSITIVITY};
+
+/// Turns mouse movement into FPS-style look for a `Camera`, meant for
+/// relative motion while the mouse is captured (`Window::set_mouse_captured`).
+///
+/// Settings are chained onto `new`:
+///
+/// - `sensitivity`: degrees per pixel of movement
+/// - `invert_y`: moving the mouse forward looks down instead of up
+/// - `smoothing`: time constant in seconds of an exponential filter on the
+///   turn rate; 0 turns it off. Smoothing spreads a movement over the
+///   following frames without losing any of it, independent of the frame rate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MouseLook {
+    sensitivity: f32,
+    invert_y: bool,
+    smoothing: f32,
+    /// Smoothed yaw and pitch rate in degrees per second.
+    velocity: (f32, f32),
+}
+
+impl Default for MouseLook {
+    fn default() -> MouseLook {
+        MouseLook {
+            sensitivity: DEFAULT_SENSITIVITY,
+            invert_y: false,
+            smoothing: 0.0,
+            velocity: (0.0, 0.0),
+        }
+    }
+}
+
+impl MouseLook {
+    pub fn new() -> MouseLook {
+        MouseLook::default()
+    }
+
+    pub fn sensitivity(mut self, degrees_per_pixel: f32) -> MouseLook {
+        self.sensitivity = degrees_per_pixel;
+        self
+    }
+
+    pub fn invert_y(mut self, invert_y: bool) -> MouseLook {
+        self.invert_y = invert_y;
+        self
+    }
+
+    /// Negative values count as 0.
+    pub fn smoothing(mut self, seconds: f32) -> MouseLook {
+        self.smoothing = seconds.max(0.0);
+        self
+    }
+
+    pub fn get_sensitivity(&self) -> f32 {
+        self.sensitivity
+    }
+
+    pub fn is_y_inverted(&self) -> bool {
+        self.invert_y
+    }
+
+    pub fn get_smoothing(&self) -> f32 {
+        self.smoothing
+    }
+
+    /// Forgets movement still being smoothed, e.g. when the mouse is
+    /// captured or released.
+    pub fn reset(&mut self) {
+        self.velocity = (0.0, 0.0);
+    }
+
+    /// The yaw and pitch in degrees for a frame of `dt` seconds in which the
+    /// mouse moved by `dx`, `dy` pixels (`+Y` downwards, as SDL reports it).
+    pub fn turn(&mut self, dx: f32, dy: f32, dt: f32) -> (f32, f32) {
+        let yaw = dx * self.sensitivity;
+        let pitch = if self.invert_y { dy } else { -dy } * self.sensitivity;
+
+        if self.smoothing <= 0.0 || dt <= 0.0 {
+            self.velocity = (0.0, 0.0);
+            return (yaw, pitch);
+        }
+
+        let (yaw, velocity) = smooth(yaw / dt, self.velocity.0, self.smoothing, dt);
+        self.velocity.0 = velocity;
+        let (pitch, velocity) = smooth(pitch / dt, self.velocity.1, self.smoothing, dt);
+        self.velocity.1 = velocity;
+        (yaw, pitch)
+    }
+
+    /// Turns `camera` by the movement of the frame; see `turn`.
+    pub fn apply(&mut self, camera: &mut Camera, dx: f32, dy: f32, dt: f32) {
+        let (yaw, pitch) = self.turn(dx, dy, dt);
+        if yaw != 0.0 || pitch != 0.0 {
+            camera.look(yaw, pitch);
+        }
+    }
+}
+
+/// Follows `rate` for `dt` seconds from `velocity`, exponentially with
+/// time constant `smoothing`. Returns the integral of the velocity over the
+/// frame and the velocity at its end; integrating exactly rather than per
+/// frame keeps the result the same at any frame rate.
+fn smooth(rate: f32, velocity: f32, smoothing: f32, dt: f32) -> (f32, f32) {
+    let decay = (-dt / smoothing).exp();
+    let turned = rate * dt + (velocity - rate) * smoothing * (1.0 - decay);
+    (turned, rate + (velocity - rate) * decay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn moving_the_mouse_forward_looks_up() {
+        let mut camera = Camera::default();
+        let mut mouse_look = MouseLook::new().sensitivity(0.5);
+
+        mouse_look.apply(&mut camera, 20.0, -10.0, 0.016);
+
+        assert!((camera.get_yaw() - (-90.0 + 10.0)).abs() < EPSILON);
+        assert!((camera.get_pitch() - 5.0).abs() < EPSILON);
+
+        let mut inverted = mouse_look.invert_y(true);
+        assert_eq!(inverted.turn(0.0, -10.0, 0.016), (0.0, -5.0));
+    }
+
+    #[test]
+    fn smoothing_delays_but_keeps_the_movement() {
+        let mut mouse_look = MouseLook::new().sensitivity(1.0).smoothing(0.05);
+        let dt = 0.01;
+
+        let (first, _) = mouse_look.turn(10.0, 0.0, dt);
+        assert!(first > 0.0 && first < 10.0);
+
+        let rest: f32 = (0..200).map(|_| mouse_look.turn(0.0, 0.0, dt).0).sum();
+        assert!((first + rest - 10.0).abs() < EPSILON, "{}", first + rest);
+    }
+
+    #[test]
+    fn smoothing_does_not_depend_on_the_frame_rate() {
+        // The same steady movement over 0.1 s at 100 and 400 frames per second
+        let total = |frames: u32| {
+            let mut mouse_look = MouseLook::new().sensitivity(1.0).smoothing(0.05);
+            let dt = 0.1 / frames as f32;
+            let pixels = 40.0 / frames as f32;
+            (0..frames).map(|_| mouse_look.turn(pixels, 0.0, dt).0).sum::<f32>()
+        };
+
+        assert!((total(10) - total(40)).abs() < EPSILON, "{} != {}", total(10), total(40));
+    }
+}
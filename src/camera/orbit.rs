@@ -0,0 +1,336 @@
+use cgmath::{Angle, Deg, InnerSpace, Matrix4, Point3, Vector3};
+use cgmath::EuclideanSpace;
+
+use sdl2::event::Event;
+
+use mesh::BoundingBox;
+
+use super::{resized_aspect_ratio, Projection, ViewCamera};
+use super::{DEFAULT_ASPECT_RATIO, DEFAULT_ZOOM, ZOOM_HIGH, ZOOM_LOW};
+
+/// Degrees per unit of `rotate`, e.g. per pixel of mouse movement.
+const DEFAULT_ROTATE_SENSITIVITY: f32 = 0.25;
+/// Fraction of the distance per unit of `pan`.
+const DEFAULT_PAN_SENSITIVITY: f32 = 0.002;
+/// Distance factor per unit of `dolly`.
+const DOLLY_STEP: f32 = 0.9;
+const MIN_DISTANCE: f32 = 0.01;
+/// Keeps the view direction off the up axis, where `look_at` breaks down.
+const ELEVATION_LIMIT: f32 = 89.0;
+
+/// A camera circling a target point, for inspecting models.
+///
+/// The camera sits `distance` away from `target`. An azimuth of 0° puts it on
+/// the `+Z` side of the target, positive azimuths move it towards `+X` and
+/// positive elevations raise it. Angles are in degrees.
+#[derive(Clone, Debug)]
+pub struct OrbitCamera {
+    target: Vector3<f32>,
+    distance: f32,
+    azimuth: f32,
+    elevation: f32,
+
+    rotate_sensitivity: f32,
+    pan_sensitivity: f32,
+    zoom: f32,
+    projection: Projection,
+    aspect_ratio: f32,
+}
+
+impl OrbitCamera {
+    pub fn new(target: Vector3<f32>, distance: f32, azimuth: f32, elevation: f32) -> OrbitCamera {
+        OrbitCamera {
+            target,
+            distance: distance.max(MIN_DISTANCE),
+            azimuth,
+            elevation: elevation.clamp(-ELEVATION_LIMIT, ELEVATION_LIMIT),
+            rotate_sensitivity: DEFAULT_ROTATE_SENSITIVITY,
+            pan_sensitivity: DEFAULT_PAN_SENSITIVITY,
+            zoom: DEFAULT_ZOOM,
+            projection: Projection::default(),
+            aspect_ratio: DEFAULT_ASPECT_RATIO,
+        }
+    }
+
+    /// An orbit camera at `position` circling `target`.
+    pub fn looking_at(position: Vector3<f32>, target: Vector3<f32>) -> OrbitCamera {
+        let offset = position - target;
+        let distance = offset.magnitude();
+        if distance < MIN_DISTANCE {
+            return OrbitCamera::new(target, MIN_DISTANCE, 0.0, 0.0);
+        }
+
+        let offset = offset / distance;
+        let azimuth = offset.x.atan2(offset.z).to_degrees();
+        let elevation = offset.y.clamp(-1.0, 1.0).asin().to_degrees();
+        OrbitCamera::new(target, distance, azimuth, elevation)
+    }
+
+    pub fn get_view(&self) -> Matrix4<f32> {
+        Matrix4::look_at(Point3::from_vec(self.get_position()),
+                         Point3::from_vec(self.target),
+                         Vector3::unit_y())
+    }
+
+    /// Vertical field of view in degrees.
+    pub fn get_zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn get_projection(&self) -> Projection {
+        self.projection
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    pub fn get_aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    /// Sets the width / height ratio of the viewport, e.g. from
+    /// `Surface::aspect_ratio` when the app starts.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+    }
+
+    /// Follows the window size; pass every event to it.
+    pub fn track_resize(&mut self, event: &Event) {
+        if let Some(aspect_ratio) = resized_aspect_ratio(event) {
+            self.aspect_ratio = aspect_ratio;
+        }
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        self.projection.matrix(self.zoom, self.aspect_ratio)
+    }
+
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.projection() * self.get_view()
+    }
+
+    pub fn get_position(&self) -> Vector3<f32> {
+        self.target + self.offset() * self.distance
+    }
+
+    pub fn get_direction(&self) -> Vector3<f32> {
+        -self.offset()
+    }
+
+    pub fn get_right(&self) -> Vector3<f32> {
+        self.get_direction().cross(Vector3::unit_y()).normalize()
+    }
+
+    pub fn get_up(&self) -> Vector3<f32> {
+        self.get_right().cross(self.get_direction()).normalize()
+    }
+
+    pub fn get_target(&self) -> Vector3<f32> {
+        self.target
+    }
+
+    pub fn get_distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn get_azimuth(&self) -> f32 {
+        self.azimuth
+    }
+
+    pub fn get_elevation(&self) -> f32 {
+        self.elevation
+    }
+
+    /// Orbits around the target, scaled by the rotate sensitivity. The
+    /// elevation stops just short of straight above or below.
+    pub fn rotate(&mut self, azimuth: f32, elevation: f32) {
+        self.azimuth = (self.azimuth + self.rotate_sensitivity * azimuth).rem_euclid(360.0);
+        self.elevation = (self.elevation + self.rotate_sensitivity * elevation)
+            .clamp(-ELEVATION_LIMIT, ELEVATION_LIMIT);
+    }
+
+    /// Moves the target along the camera's right and up axes. Steps grow
+    /// with the distance, so panning feels the same at any scale.
+    pub fn pan(&mut self, right: f32, up: f32) {
+        let step = self.pan_sensitivity * self.distance;
+        self.target += (self.get_right() * right + self.get_up() * up) * step;
+    }
+
+    /// Moves towards the target for positive `amount` (e.g. mouse wheel
+    /// steps) and away from it for negative ones, never reaching it.
+    pub fn dolly(&mut self, amount: f32) {
+        self.distance = (self.distance * DOLLY_STEP.powf(amount)).max(MIN_DISTANCE);
+    }
+
+    /// Sets the field of view to `zoom` degrees, clamped to
+    /// `ZOOM_LOW..=ZOOM_HIGH`.
+    pub fn move_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(ZOOM_LOW, ZOOM_HIGH);
+    }
+
+    /// Narrows the field of view by `amount` degrees (negative values widen
+    /// it), e.g. by mouse wheel steps. Clamped like `move_zoom`.
+    pub fn zoom_by(&mut self, amount: f32) {
+        let zoom = self.zoom - amount;
+        self.move_zoom(zoom);
+    }
+
+    /// Targets the center of `bounds` and backs off until its bounding sphere
+    /// fits the view. An orthographic view is resized to fit the sphere
+    /// instead. Keeps the angles and the zoom.
+    pub fn zoom_to_fit(&mut self, bounds: &BoundingBox) {
+        let half_vertical = Deg(self.zoom * 0.5);
+        let half_horizontal = Deg::atan(half_vertical.tan() * self.aspect_ratio);
+        let half_fov = if half_horizontal < half_vertical { half_horizontal } else { half_vertical };
+
+        self.target = bounds.center();
+        self.distance = (bounds.radius() / half_fov.sin()).max(MIN_DISTANCE);
+
+        if let Projection::Orthographic { ref mut height, .. } = self.projection {
+            // The height shown at the current zoom is 2 * radius, or more when
+            // the width is the narrower side
+            let visible = 2.0 * bounds.radius() / self.aspect_ratio.min(1.0);
+            *height = visible * DEFAULT_ZOOM / self.zoom;
+        }
+    }
+
+    /// Unit vector from the target towards the camera.
+    fn offset(&self) -> Vector3<f32> {
+        let (azimuth, elevation) = (Deg(self.azimuth), Deg(self.elevation));
+        Vector3::new(elevation.cos() * azimuth.sin(),
+                     elevation.sin(),
+                     elevation.cos() * azimuth.cos())
+    }
+}
+
+impl ViewCamera for OrbitCamera {
+    fn get_view(&self) -> Matrix4<f32> {
+        OrbitCamera::get_view(self)
+    }
+
+    fn get_zoom(&self) -> f32 {
+        OrbitCamera::get_zoom(self)
+    }
+
+    fn get_position(&self) -> Vector3<f32> {
+        OrbitCamera::get_position(self)
+    }
+
+    fn get_direction(&self) -> Vector3<f32> {
+        OrbitCamera::get_direction(self)
+    }
+
+    fn projection(&self) -> Matrix4<f32> {
+        OrbitCamera::projection(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_vec_eq(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).magnitude() < EPSILON, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn angles_place_the_camera() {
+        let target = Vector3::new(1.0, 2.0, 3.0);
+
+        assert_vec_eq(OrbitCamera::new(target, 2.0, 0.0, 0.0).get_position(), target + Vector3::new(0.0, 0.0, 2.0));
+        assert_vec_eq(OrbitCamera::new(target, 2.0, 90.0, 0.0).get_position(), target + Vector3::new(2.0, 0.0, 0.0));
+
+        let raised = OrbitCamera::new(target, 2.0, 0.0, 30.0);
+        assert_vec_eq(raised.get_position(), target + Vector3::new(0.0, 1.0, 3.0f32.sqrt()));
+    }
+
+    #[test]
+    fn view_looks_at_the_target() {
+        let cam = OrbitCamera::new(Vector3::new(1.0, 0.0, -2.0), 5.0, 40.0, 20.0);
+        let target = cam.get_view() * cam.get_target().extend(1.0);
+
+        assert_vec_eq(target.truncate(), Vector3::new(0.0, 0.0, -5.0));
+    }
+
+    #[test]
+    fn looking_at_recovers_the_angles() {
+        let cam = OrbitCamera::new(Vector3::new(0.5, 0.0, 0.0), 3.0, -60.0, 25.0);
+        let copy = OrbitCamera::looking_at(cam.get_position(), cam.get_target());
+
+        assert!((copy.get_distance() - 3.0).abs() < EPSILON);
+        assert!((copy.get_azimuth() - -60.0).abs() < EPSILON);
+        assert!((copy.get_elevation() - 25.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn rotate_clamps_the_elevation() {
+        let mut cam = OrbitCamera::new(Vector3::new(0.0, 0.0, 0.0), 1.0, 0.0, 0.0);
+
+        cam.rotate(4.0, 1000.0);
+
+        assert_eq!(cam.get_azimuth(), 4.0 * DEFAULT_ROTATE_SENSITIVITY);
+        assert_eq!(cam.get_elevation(), ELEVATION_LIMIT);
+        assert!(cam.get_up().magnitude() > 0.5);
+    }
+
+    #[test]
+    fn pan_moves_camera_and_target_together() {
+        let mut cam = OrbitCamera::new(Vector3::new(0.0, 0.0, 0.0), 10.0, 30.0, 10.0);
+        let position = cam.get_position();
+        let right = cam.get_right();
+
+        cam.pan(50.0, 0.0);
+
+        let moved = right * 50.0 * DEFAULT_PAN_SENSITIVITY * 10.0;
+        assert_vec_eq(cam.get_target(), moved);
+        assert_vec_eq(cam.get_position(), position + moved);
+    }
+
+    #[test]
+    fn dolly_never_reaches_the_target() {
+        let mut cam = OrbitCamera::new(Vector3::new(0.0, 0.0, 0.0), 10.0, 0.0, 0.0);
+
+        cam.dolly(1.0);
+        assert!((cam.get_distance() - 10.0 * DOLLY_STEP).abs() < EPSILON);
+
+        cam.dolly(1000.0);
+        assert_eq!(cam.get_distance(), MIN_DISTANCE);
+    }
+
+    #[test]
+    fn zoom_to_fit_keeps_the_bounds_in_view() {
+        let bounds = BoundingBox::new(Vector3::new(-1.0, 0.0, -1.0), Vector3::new(1.0, 2.0, 1.0));
+        let mut cam = OrbitCamera::new(Vector3::new(5.0, 5.0, 5.0), 1.0, 0.0, 0.0);
+        cam.set_aspect_ratio(2.0);
+
+        cam.zoom_to_fit(&bounds);
+
+        assert_vec_eq(cam.get_target(), Vector3::new(0.0, 1.0, 0.0));
+        // The sphere touches the top and bottom edges of the 45° view
+        let half_fov = Deg(DEFAULT_ZOOM * 0.5);
+        assert!((cam.get_distance() * half_fov.sin() - bounds.radius()).abs() < EPSILON);
+
+        // In a portrait view the horizontal field of view is the narrower one
+        cam.set_aspect_ratio(0.5);
+        cam.zoom_to_fit(&bounds);
+        assert!(cam.get_distance() * half_fov.sin() > bounds.radius() + EPSILON);
+    }
+
+    #[test]
+    fn zoom_to_fit_resizes_orthographic_views() {
+        let bounds = BoundingBox::new(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0));
+        let mut cam = OrbitCamera::new(Vector3::new(0.0, 0.0, 0.0), 1.0, 0.0, 0.0);
+        cam.set_projection(Projection::orthographic(1.0, 0.1, 100.0));
+        cam.set_aspect_ratio(0.5);
+
+        cam.zoom_to_fit(&bounds);
+
+        match cam.get_projection() {
+            Projection::Orthographic { height, .. } => assert!((height - 4.0 * bounds.radius()).abs() < EPSILON),
+            projection => panic!("unexpected projection {:?}", projection),
+        }
+    }
+}
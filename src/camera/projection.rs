@@ -0,0 +1,74 @@
+use cgmath::{ortho, perspective, Deg, Matrix4, Rad};
+
+use super::DEFAULT_ZOOM;
+
+const DEFAULT_NEAR: f32 = 0.1;
+const DEFAULT_FAR: f32 = 100.0;
+
+/// How a camera maps view space to clip space. The width of the view
+/// follows the aspect ratio of the viewport.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// The vertical field of view is the camera's zoom.
+    Perspective { near: f32, far: f32 },
+    /// Shows `height` units vertically at the default zoom; zooming in
+    /// narrows the view like it narrows the field of view.
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Default for Projection {
+    fn default() -> Projection {
+        Projection::Perspective { near: DEFAULT_NEAR, far: DEFAULT_FAR }
+    }
+}
+
+impl Projection {
+    pub fn perspective(near: f32, far: f32) -> Projection {
+        Projection::Perspective { near, far }
+    }
+
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Projection {
+        Projection::Orthographic { height, near, far }
+    }
+
+    /// The projection matrix for a camera with a vertical field of view of
+    /// `zoom` degrees and a viewport of `aspect_ratio` (width / height).
+    pub fn matrix(&self, zoom: f32, aspect_ratio: f32) -> Matrix4<f32> {
+        match *self {
+            Projection::Perspective { near, far } => {
+                perspective(Rad::from(Deg(zoom)), aspect_ratio, near, far)
+            },
+            Projection::Orthographic { height, near, far } => {
+                let half_height = height * 0.5 * zoom / DEFAULT_ZOOM;
+                let half_width = half_height * aspect_ratio;
+                ortho(-half_width, half_width, -half_height, half_height, near, far)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::Vector4;
+
+    #[test]
+    fn orthographic_extents_follow_aspect_and_zoom() {
+        let projection = Projection::orthographic(4.0, 0.1, 10.0);
+
+        // The corner of a 2:1 view 4 units high
+        let corner = projection.matrix(DEFAULT_ZOOM, 2.0) * Vector4::new(4.0, 2.0, -1.0, 1.0);
+        assert!((corner.x - 1.0).abs() < 1e-6 && (corner.y - 1.0).abs() < 1e-6);
+
+        // Half the zoom shows half the extent
+        let corner = projection.matrix(DEFAULT_ZOOM * 0.5, 2.0) * Vector4::new(2.0, 1.0, -1.0, 1.0);
+        assert!((corner.x - 1.0).abs() < 1e-6 && (corner.y - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn perspective_uses_the_zoom_as_field_of_view() {
+        assert_eq!(Projection::default().matrix(60.0, 1.5),
+                   perspective(Rad::from(Deg(60.0)), 1.5, DEFAULT_NEAR, DEFAULT_FAR));
+    }
+}
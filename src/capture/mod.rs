@@ -0,0 +1,141 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::RgbImage;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+use window::Window;
+
+/// Key `ScreenshotHotkey` reacts to.
+pub const SCREENSHOT_KEY: Keycode = Keycode::F12;
+
+/// Reads `width` x `height` pixels of `framebuffer` (0 for the default
+/// framebuffer) into an image, top row first.
+pub fn capture(framebuffer: GLuint, width: u32, height: u32) -> RgbImage {
+    let pixels = read_pixels(framebuffer, width, height, gl::RGB, 3);
+    RgbImage::from_raw(width, height, pixels).unwrap()
+}
+
+/// Like `capture`, reading whatever framebuffer is bound for reading.
+pub fn capture_bound(width: u32, height: u32) -> RgbImage {
+    let mut framebuffer = 0;
+    unsafe {
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut framebuffer);
+    }
+    capture(framebuffer as GLuint, width, height)
+}
+
+/// Captures the default framebuffer and writes it to `path`. The format is
+/// picked from the extension (`.png`, `.jpg`/`.jpeg`).
+pub fn save_screenshot<P: AsRef<Path>>(path: P, width: u32, height: u32) -> io::Result<()> {
+    capture(0, width, height).save(path)
+}
+
+/// `screenshot-<unix time in ms>.png` inside `dir`.
+pub fn timestamped_path<P: AsRef<Path>>(dir: P) -> PathBuf {
+    let millis = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or(0);
+    dir.as_ref().join(format!("screenshot-{}.png", millis))
+}
+
+/// Saves a screenshot into the working directory after `SCREENSHOT_KEY` was
+/// pressed. The back buffer is undefined after a swap, so the key press only
+/// records the request and the capture happens between rendering and `swap`.
+#[derive(Debug, Default)]
+pub struct ScreenshotHotkey {
+    requested: bool,
+}
+
+impl ScreenshotHotkey {
+    pub fn new() -> ScreenshotHotkey {
+        ScreenshotHotkey::default()
+    }
+
+    /// Returns true if `event` was the hotkey and has been consumed.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::KeyDown { keycode: Some(SCREENSHOT_KEY), repeat: false, .. } => {
+                self.requested = true;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Call after rendering a frame and before `Window::swap`.
+    pub fn capture_if_requested(&mut self, window: &Window) {
+        if !self.requested {
+            return;
+        }
+        self.requested = false;
+
+        let path = timestamped_path(".");
+        match window.save_screenshot(&path) {
+            Ok(()) => println!("saved screenshot to {}", path.display()),
+            Err(err) => eprintln!("failed to save screenshot to {}: {}", path.display(), err),
+        }
+    }
+}
+
+/// Reads tightly packed pixels of `framebuffer` and returns them top row
+/// first. The previous read framebuffer binding is restored afterwards.
+pub(crate) fn read_pixels(framebuffer: GLuint, width: u32, height: u32, format: GLenum, components: usize) -> Vec<u8> {
+    let row = width as usize * components;
+    let mut pixels = vec![0u8; row * height as usize];
+
+    unsafe {
+        let mut previous = 0;
+        gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous);
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer);
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(0, 0, width as GLsizei, height as GLsizei,
+                       format, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _);
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, previous as GLuint);
+    }
+
+    flip_rows(&mut pixels, row);
+    pixels
+}
+
+/// GL's origin is the bottom left corner, images start at the top.
+fn flip_rows(pixels: &mut [u8], row: usize) {
+    if row == 0 {
+        return;
+    }
+    let rows = pixels.len() / row;
+    for top in 0..rows / 2 {
+        let bottom = rows - 1 - top;
+        let (upper, lower) = pixels.split_at_mut(bottom * row);
+        upper[top * row..(top + 1) * row].swap_with_slice(&mut lower[..row]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_flipped() {
+        let mut pixels = vec![1, 1, 2, 2, 3, 3];
+        flip_rows(&mut pixels, 2);
+        assert_eq!(pixels, [3, 3, 2, 2, 1, 1]);
+
+        let mut pixels = vec![1, 2, 3, 4];
+        flip_rows(&mut pixels, 2);
+        assert_eq!(pixels, [3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn screenshot_path_is_png() {
+        let path = timestamped_path("shots");
+        assert!(path.starts_with("shots"));
+        assert_eq!(path.extension().unwrap(), "png");
+    }
+}
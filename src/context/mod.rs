@@ -0,0 +1,201 @@
+use std::cell::Cell;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+static ALIVE: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+/// Deletions waiting for their context to be current again.
+static PENDING: Mutex<Vec<(usize, GlObject)>> = Mutex::new(Vec::new());
+
+thread_local! {
+    static CURRENT: Cell<Option<ContextId>> = const { Cell::new(None) };
+}
+
+/// The name of a GL object, for deleting it in the right context.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlObject {
+    Buffer(GLuint),
+    VertexArray(GLuint),
+    Texture(GLuint),
+    Program(GLuint),
+}
+
+impl GlObject {
+    /// The context the name belongs to has to be current.
+    unsafe fn delete(self) {
+        match self {
+            GlObject::Buffer(id) => gl::DeleteBuffers(1, &id),
+            GlObject::VertexArray(id) => gl::DeleteVertexArrays(1, &id),
+            GlObject::Texture(id) => gl::DeleteTextures(1, &id),
+            GlObject::Program(id) => gl::DeleteProgram(id),
+        }
+    }
+}
+
+/// Identifies one OpenGL context for as long as the process runs.
+///
+/// GL objects remember the context they were created in so they can skip
+/// deleting names in a context that no longer exists and catch use after the
+/// context was torn down.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ContextId(usize);
+
+impl ContextId {
+    /// Registers a freshly created context and makes it current on this thread.
+    ///
+    /// `Window` does this itself; call it when a context is created some other way.
+    pub fn register() -> ContextId {
+        let id = ContextId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+        ALIVE.lock().unwrap().push(id.0);
+        id.make_current();
+        id
+    }
+
+    /// Marks the context as destroyed. Objects created in it stop touching GL.
+    pub fn unregister(self) {
+        ALIVE.lock().unwrap().retain(|&alive| alive != self.0);
+        // Destroying the context deleted its objects
+        PENDING.lock().unwrap().retain(|&(context, _)| context != self.0);
+        CURRENT.with(|current| {
+            if current.get() == Some(self) {
+                current.set(None);
+            }
+        });
+    }
+
+    /// Records that this context is now current on the calling thread, and
+    /// carries out the deletions that were waiting for it.
+    pub fn make_current(self) {
+        CURRENT.with(|current| current.set(Some(self)));
+
+        let pending: Vec<GlObject> = {
+            let mut pending = PENDING.lock().unwrap();
+            let (mine, others) = pending.drain(..).partition(|&(context, _)| context == self.0);
+            *pending = others;
+            mine.into_iter().map(|(_, object)| object).collect()
+        };
+        for object in pending {
+            unsafe {
+                object.delete();
+            }
+        }
+    }
+
+    /// The context current on this thread.
+    ///
+    /// # Panics
+    ///
+    /// If no registered context is current, since every GL call would be invalid.
+    pub fn current() -> ContextId {
+        CURRENT.with(|current| current.get())
+            .expect("no OpenGL context is current on this thread")
+    }
+
+    pub fn is_alive(self) -> bool {
+        ALIVE.lock().unwrap().contains(&self.0)
+    }
+
+    /// Whether this context is alive and current on the calling thread, so
+    /// GL names created in it refer to its objects.
+    pub fn is_current(self) -> bool {
+        CURRENT.with(|current| current.get()) == Some(self) && self.is_alive()
+    }
+
+    /// Deletes `object`, which was created in this context. Names are only
+    /// valid in their own context, so while another one is current the
+    /// deletion waits until this one is made current again; objects of a
+    /// destroyed context are already gone.
+    pub fn delete(self, object: GlObject) {
+        if self.is_current() {
+            unsafe {
+                object.delete();
+            }
+        } else if self.is_alive() {
+            PENDING.lock().unwrap().push((self.0, object));
+        }
+    }
+
+    /// Panics with a description of `what` if the context has been destroyed.
+    pub fn assert_alive(self, what: &str) {
+        assert!(self.is_alive(), "{} used after its OpenGL context was destroyed", what);
+    }
+}
+
+/// Anything frames are rendered into: a `Window` or a `HeadlessContext`.
+pub trait Surface {
+    fn context_id(&self) -> ContextId;
+
+    /// Size of the framebuffer in pixels.
+    fn drawable_size(&self) -> (u32, u32);
+
+    fn aspect_ratio(&self) -> f32 {
+        let (width, height) = self.drawable_size();
+        width as f32 / height.max(1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_context_is_current_until_unregistered() {
+        let context = ContextId::register();
+        assert!(context.is_alive());
+        assert_eq!(ContextId::current(), context);
+
+        context.unregister();
+        assert!(!context.is_alive());
+        assert!(CURRENT.with(|current| current.get()).is_none());
+    }
+
+    #[test]
+    fn contexts_get_distinct_ids() {
+        let first = ContextId::register();
+        let second = ContextId::register();
+        assert_ne!(first, second);
+        assert_eq!(ContextId::current(), second);
+
+        second.unregister();
+        assert!(first.is_alive());
+        first.unregister();
+    }
+
+    fn pending(context: ContextId) -> Vec<GlObject> {
+        PENDING.lock().unwrap().iter()
+            .filter(|&&(id, _)| id == context.0)
+            .map(|&(_, object)| object)
+            .collect()
+    }
+
+    #[test]
+    fn deletes_wait_for_their_context() {
+        let first = ContextId::register();
+        let second = ContextId::register();
+        assert!(!first.is_current());
+
+        // Deleting now would hit the second context's object of that name
+        first.delete(GlObject::Buffer(7));
+        assert_eq!(pending(first), vec![GlObject::Buffer(7)]);
+        assert!(pending(second).is_empty());
+
+        // The context took its objects with it
+        first.unregister();
+        assert!(pending(first).is_empty());
+        first.delete(GlObject::Texture(3));
+        assert!(pending(first).is_empty());
+
+        second.unregister();
+    }
+
+    #[test]
+    #[should_panic(expected = "used after its OpenGL context was destroyed")]
+    fn use_after_teardown_panics() {
+        let context = ContextId::register();
+        context.unregister();
+        context.assert_alive("Buffer");
+    }
+}
@@ -1,87 +1,74 @@
 extern crate cgmath;
-extern crate image;
+extern crate opengl_sdl2_demo;
 extern crate sdl2;
 
-#[path="../../shader/mod.rs"]
-mod shader;
-use shader::Shader;
+use opengl_sdl2_demo::app::{self, App, Flow, Timestep, Timing};
+use opengl_sdl2_demo::camera::{Camera, CameraMovement, MouseLook, Projection};
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::gl_gen::gl::types::*;
+use opengl_sdl2_demo::impl_vertex;
+use opengl_sdl2_demo::input::{Bindings, InputState};
+use opengl_sdl2_demo::mesh::Mesh;
+use opengl_sdl2_demo::shader::ReloadableShader;
+use opengl_sdl2_demo::texture::{MinFilter, Texture2D};
+use opengl_sdl2_demo::window::Window;
 
-#[path="../../gl_gen/mod.rs"]
-mod gl_gen;
-use gl_gen::gl;
-use gl_gen::gl::types::*;
-
-#[path="../../camera/mod.rs"]
-mod camera;
-use camera::Camera;
-use camera::CameraMovement;
-
-use std::ffi::CString;
-use std::mem;
-use std::os::raw::c_void;
-use std::path::Path;
-use std::ptr;
-use std::time::Duration;
-
-use cgmath::{Deg, Matrix, Matrix4, perspective, Rad, Vector3};
-
-use image::GenericImage;
+use cgmath::{Deg, Matrix4, Vector3};
 
 use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::video::GLProfile;
 
-fn find_sdl_gl_driver() -> Option<u32> {
-    for (index, item) in sdl2::render::drivers().enumerate() {
-        if item.name == "opengl" {
-            return Some(index as u32);
-        }
-    }
-    None
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct TexturedVertex {
+    position: [GLfloat; 3],
+    tex_coords: [GLfloat; 2],
 }
 
-static VERTEX_DATA: [GLfloat; 180] = [
-    -0.5, -0.5, -0.5,  0.0, 0.0,
-     0.5, -0.5, -0.5,  1.0, 0.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-    -0.5,  0.5, -0.5,  0.0, 1.0,
-    -0.5, -0.5, -0.5,  0.0, 0.0,
-
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-     0.5, -0.5,  0.5,  1.0, 0.0,
-     0.5,  0.5,  0.5,  1.0, 1.0,
-     0.5,  0.5,  0.5,  1.0, 1.0,
-    -0.5,  0.5,  0.5,  0.0, 1.0,
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-
-    -0.5,  0.5,  0.5,  1.0, 0.0,
-    -0.5,  0.5, -0.5,  1.0, 1.0,
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-    -0.5,  0.5,  0.5,  1.0, 0.0,
-
-     0.5,  0.5,  0.5,  1.0, 0.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-     0.5, -0.5, -0.5,  0.0, 1.0,
-     0.5, -0.5, -0.5,  0.0, 1.0,
-     0.5, -0.5,  0.5,  0.0, 0.0,
-     0.5,  0.5,  0.5,  1.0, 0.0,
-
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-     0.5, -0.5, -0.5,  1.0, 1.0,
-     0.5, -0.5,  0.5,  1.0, 0.0,
-     0.5, -0.5,  0.5,  1.0, 0.0,
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-
-    -0.5,  0.5, -0.5,  0.0, 1.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-     0.5,  0.5,  0.5,  1.0, 0.0,
-     0.5,  0.5,  0.5,  1.0, 0.0,
-    -0.5,  0.5,  0.5,  0.0, 0.0,
-    -0.5,  0.5, -0.5,  0.0, 1.0
+impl_vertex!(TexturedVertex { position: 0, tex_coords: 2 });
+
+static VERTICES: [TexturedVertex; 36] = [
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 0.0] },
+
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [0.0, 1.0] },
 ];
 
 static CUBE_POS: [(f32, f32, f32); 10] = [
@@ -97,187 +84,173 @@ static CUBE_POS: [(f32, f32, f32); 10] = [
     (-1.3,  1.0, -1.5)  
 ];
 
-fn main() {
-    
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let mut timer = sdl_context.timer().unwrap();
-
-    #[cfg(target_os = "macos")]
-    video_subsystem.gl_attr().set_context_profile(GLProfile::Core);
-
-    let window = video_subsystem.window("Window", 800, 600)
-        .resizable()
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
-
-    let canvas = window.into_canvas()
-        .index(find_sdl_gl_driver().unwrap())
-        .present_vsync()
-        .build()
-        .unwrap();
-
-    gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
-    canvas.window().gl_set_context_to_current().unwrap();
-
-    let mut event_pump = sdl_context.event_pump().unwrap();
-
-    let mut vao = 0;
-    let mut vbo = 0;
-    let mut texture1 = 0;
-    let mut texture2 = 0;
-
-    let shader = Shader::from_source("src/examples/camera/shader/camera.glslv", "src/examples/camera/shader/camera.glslf");
-
-    let texture_image1 = image::open(&Path::new("resources/container.jpg")).unwrap();
-    let texture_image2 = image::open(&Path::new("resources/awesomeface.png")).unwrap();
-
-    let camera = Camera::new(Vector3::new(0.0, 0.0, 3.0), 
-                             Vector3::new(0.0, 1.0, 0.0),
-                             0.0, -90.0);
-
-    unsafe {
-        gl::Enable(gl::DEPTH_TEST);  
-
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
-
-        // VAO
-        gl::BindVertexArray(vao);
-
-        // VBO
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, 
-                        ((VERTEX_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                        mem::transmute(&VERTEX_DATA[0]),
-                        gl::STATIC_DRAW);   
-
-        // Position attribute
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<GLfloat>()) as i32, ptr::null());
-        gl::EnableVertexAttribArray(0);
-
-        // Texture attribute
-        gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<GLfloat>()) as i32, (3 * mem::size_of::<GLfloat>()) as *const _);
-        gl::EnableVertexAttribArray(2); 
-        
-        gl::BindVertexArray(0);
-
-        // Texture
-        gl::GenTextures(1, &mut texture1);
-        gl::BindTexture(gl::TEXTURE_2D, texture1);
-
-        // Set the texture wrapping parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);	// Set texture wrapping to GL_REPEAT (usually basic wrapping method)
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-
-        // Set texture filtering parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+/// Degrees per second while rolling.
+const ROLL_SPEED: f32 = 90.0;
+/// Movement speed factor while `boost` is held.
+const BOOST: f32 = 3.0;
+/// Degrees per second with a stick fully deflected.
+const TURN_SPEED: f32 = 120.0;
+/// Degrees of field of view per second with a trigger fully pulled.
+const ZOOM_SPEED: f32 = 30.0;
+
+pub struct CameraClass {
+    mesh: Mesh,
+    texture1: Texture2D,
+    texture2: Texture2D,
+    reloadable_shader: ReloadableShader,
+    camera: Camera,
+    mouse_look: MouseLook,
+    bindings: Bindings,
+}
 
+impl App for CameraClass {
+    fn init(surface: &dyn Surface) -> CameraClass {
+        let reloadable_shader = ReloadableShader::from_source("src/examples/camera/shader/camera.glslv", "src/examples/camera/shader/camera.glslf")
+            .unwrap_or_else(|err| panic!("{}", err));
 
-        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, texture_image1.width() as i32, 
-                       texture_image1.height() as i32, 0, gl::RGB, gl::UNSIGNED_BYTE, 
-                       texture_image1.to_rgb().into_raw().as_ptr() as *const c_void);
+        let textures = Texture2D::builder().min_filter(MinFilter::Linear);
+        let texture1 = textures.open("resources/container.jpg")
+            .unwrap_or_else(|err| panic!("{}", err));
+        let texture2 = textures.open("resources/awesomeface.png")
+            .unwrap_or_else(|err| panic!("{}", err));
 
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-        gl::BindTexture(gl::TEXTURE_2D, 0);
+        let mut camera = Camera::new(Vector3::new(0.0, 0.0, 3.0), 
+                                     Vector3::new(0.0, 1.0, 0.0),
+                                     0.0, -90.0);
+        camera.set_aspect_ratio(surface.aspect_ratio());
 
-        gl::GenTextures(1, &mut texture2);
-        gl::BindTexture(gl::TEXTURE_2D, texture2);
+        let mesh = Mesh::new(&VERTICES);
 
-        // Set the texture wrapping parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);	// Set texture wrapping to GL_REPEAT (usually basic wrapping method)
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+        let bindings = Bindings::load("resources/bindings/camera.bindings")
+            .unwrap_or_else(|err| panic!("{}", err));
 
-        // Set texture filtering parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+        }
 
-        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, texture_image2.width() as i32, 
-                       texture_image2.height() as i32, 0, gl::RGB, gl::UNSIGNED_BYTE, 
-                       texture_image2.to_rgb().into_raw().as_ptr() as *const c_void);
+        CameraClass {
+            mesh,
+            texture1,
+            texture2,
+            reloadable_shader,
+            camera,
+            mouse_look: MouseLook::new().sensitivity(0.1).smoothing(0.02),
+            bindings,
+        }
+    }
 
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-        gl::BindTexture(gl::TEXTURE_2D, 0);
+    fn update(&mut self, _surface: &dyn Surface, dt: f32) {
+        self.camera.update(dt);
 
+        // Pick up edits to the .glslv/.glslf files while running
+        if let Err(err) = self.reloadable_shader.reload_if_changed() {
+            eprintln!("{}", err);
+        }
     }
 
-    let mut delta_tick: f32 = 0.0;
-    let mut last_tick: f32 = 0.0;
+    fn render(&mut self, _surface: &dyn Surface) {
+        let shader = self.reloadable_shader.shader();
 
-    'running: loop {        
         unsafe {
             // Clear the screen to black
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, texture1);
-            gl::Uniform1i(gl::GetUniformLocation(shader.program(), CString::new("ourTexture1").unwrap().as_ptr()), 0);
-
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, texture2);
-            gl::Uniform1i(gl::GetUniformLocation(shader.program(), CString::new("ourTexture2").unwrap().as_ptr()), 1);
-
             shader.gl_use();
 
-            let ticks = (timer.ticks() as f32) / 1000.0;
-            delta_tick = ticks - last_tick;
-            last_tick = ticks;
+            self.texture1.bind_to_unit(0);
+            shader.set_sampler("ourTexture1", 0);
 
-            let model = Matrix4::from_angle_x(Deg(50.0 * ticks)) * Matrix4::from_angle_y(Deg(50.0 * ticks));
-            let view = camera.get_view();
-            let projection = perspective(Rad::from(Deg(camera.get_zoom())), 1.33, 0.1, 100.0);
+            self.texture2.bind_to_unit(1);
+            shader.set_sampler("ourTexture2", 1);
 
-            let model_loc = gl::GetUniformLocation(shader.program(), CString::new("model").unwrap().as_ptr());
-            gl::UniformMatrix4fv(model_loc, 1, gl::FALSE, model.as_ptr());
+            let view = self.camera.get_view();
+            let projection = self.camera.projection();
 
-            let view_loc = gl::GetUniformLocation(shader.program(), CString::new("view").unwrap().as_ptr());
-            gl::UniformMatrix4fv(view_loc, 1, gl::FALSE, view.as_ptr());
+            shader.set_mat4("view", &view);
+            shader.set_mat4("projection", &projection);
 
-            let projection_loc = gl::GetUniformLocation(shader.program(), CString::new("projection").unwrap().as_ptr());
-            gl::UniformMatrix4fv(projection_loc, 1, gl::FALSE, projection.as_ptr());
-
-            gl::BindVertexArray(vao);
-            for i in 0..CUBE_POS.len() {
-                let model = Matrix4::from_translation(Vector3::new(CUBE_POS[i].0, CUBE_POS[i].1, CUBE_POS[i].2)) 
+            for (i, pos) in CUBE_POS.iter().enumerate() {
+                let model = Matrix4::from_translation(Vector3::new(pos.0, pos.1, pos.2)) 
                             * Matrix4::from_angle_x(Deg(20.0 * i as f32))
                             * 0.3 * Matrix4::from_angle_y(Deg(20.0 * i as f32)) * 0.5 * Matrix4::from_angle_z(Deg(20.0 * i as f32));
-                gl::UniformMatrix4fv(model_loc, 1, gl::FALSE, model.as_ptr());
+                shader.set_mat4("model", &model);
 
-                gl::DrawArrays(gl::TRIANGLES, 0, 36);
+                self.mesh.draw();
             }
         }
+    }
 
-        canvas.window().gl_swap_window();
-
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit{..} => break 'running,
-                Event::KeyDown{keycode: key, ..} => {
-                    match key.unwrap() {
-                        Keycode::W => {camera.move_position(CameraMovement::FORWARD, delta_tick); println!("w pressed!");},
-                        Keycode::A => {camera.move_position(CameraMovement::LEFT, delta_tick)},
-                        Keycode::S => {camera.move_position(CameraMovement::BACKWARD, delta_tick)},
-                        Keycode::D => {camera.move_position(CameraMovement::RIGHT, delta_tick)},
-                        _ => {},
-                    }
-                },
-                Event::MouseMotion{x: xpos, y: ypos,..} => {
-                    println!("{:?},{:?}", xpos, ypos);
-                    camera.move_rotation(xpos as f32, ypos as f32);
-                }
-                _ => {},
-            }
+    fn handle_input(&mut self, window: &Window, input: &InputState, dt: f32) {
+        let actions = input.actions(&self.bindings);
+
+        if actions.was_pressed("capture_mouse") {
+            window.set_mouse_captured(!window.is_mouse_captured());
+            self.mouse_look.reset();
+        }
+        if actions.was_pressed("invert_look") {
+            self.mouse_look = self.mouse_look.invert_y(!self.mouse_look.is_y_inverted());
+        }
+        if window.is_mouse_captured() {
+            self.mouse_look.apply(&mut self.camera, actions.value("look_x"), actions.value("look_y"), dt);
+        }
+
+        // Movement follows the keys for as long as they are held
+        let step = if actions.is_down("boost") { BOOST * dt } else { dt };
+        self.camera.move_position(CameraMovement::FORWARD, actions.value("move_forward") * step);
+        self.camera.move_position(CameraMovement::RIGHT, actions.value("move_right") * step);
+
+        let vertical = actions.value("move_up");
+        if actions.is_down("trigger_zoom") {
+            self.camera.zoom_by(vertical * ZOOM_SPEED * dt);
+        } else {
+            self.camera.move_position(CameraMovement::UP, vertical * step);
+        }
+
+        let (turn_right, turn_up) = (actions.value("turn_right"), actions.value("turn_up"));
+        if turn_right != 0.0 || turn_up != 0.0 {
+            self.camera.look(turn_right * TURN_SPEED * dt, turn_up * TURN_SPEED * dt);
+        }
+
+        let roll = actions.value("roll");
+        if roll != 0.0 {
+            self.camera.rotate_local(0.0, 0.0, roll * ROLL_SPEED * dt);
+        }
+        self.camera.zoom_by(actions.value("zoom"));
+
+        if actions.was_pressed("turn_to_center") {
+            // Swing round to the cube in the middle
+            self.camera.turn_towards(Vector3::new(0.0, 0.0, 0.0), 1.0);
+        }
+        if actions.was_pressed("toggle_projection") {
+            let projection = match self.camera.get_projection() {
+                Projection::Perspective { .. } => Projection::orthographic(6.0, 0.1, 100.0),
+                Projection::Orthographic { .. } => Projection::default(),
+            };
+            self.camera.set_projection(projection);
         }
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
     }
 
-    // Cleanup
-    unsafe {
-        gl::DeleteVertexArrays(1, &vao);
-        gl::DeleteBuffers(1, &vbo);
+    fn handle_event(&mut self, _window: &Window, event: &Event) -> Flow {
+        self.camera.track_resize(event);
+
+        match *event {
+            Event::Quit{..} => Flow::Exit,
+            _ => Flow::Continue,
+        }
     }
-}
\ No newline at end of file
+}
+
+fn main() {
+    let window = Window::builder("Camera")
+        .msaa(4)
+        .build()
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    // Turns advance in fixed steps; the frame rate is printed every second
+    let timing = Timing::new()
+        .timestep(Timestep::Fixed(1.0 / 120.0))
+        .print_stats(true);
+
+    app::run_with::<CameraClass>(window, timing);
+}
@@ -1,82 +1,75 @@
 extern crate cgmath;
-extern crate image;
+extern crate opengl_sdl2_demo;
 extern crate sdl2;
 
-#[path="../../shader/mod.rs"]
-mod shader;
-use shader::Shader;
+use opengl_sdl2_demo::app::{self, App, Flow};
+use opengl_sdl2_demo::camera::Camera;
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::gl_gen::gl::types::*;
+use opengl_sdl2_demo::impl_vertex;
+use opengl_sdl2_demo::mesh::Mesh;
+use opengl_sdl2_demo::shader::Shader;
+use opengl_sdl2_demo::skybox::Skybox;
+use opengl_sdl2_demo::texture::{CubeMap, MinFilter, Texture2D};
+use opengl_sdl2_demo::window::Window;
 
-#[path="../../gl_gen/mod.rs"]
-mod gl_gen;
-use gl_gen::gl;
-use gl_gen::gl::types::*;
-
-use std::ffi::CString;
-use std::mem;
-use std::os::raw::c_void;
-use std::path::Path;
-use std::ptr;
-use std::time::Duration;
-
-use cgmath::{Deg, Matrix, Matrix4, perspective, Rad, Vector3};
-
-use image::GenericImage;
+use cgmath::{Deg, Matrix4, Vector3};
 
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
-use sdl2::video::GLProfile;
 
-fn find_sdl_gl_driver() -> Option<u32> {
-    for (index, item) in sdl2::render::drivers().enumerate() {
-        if item.name == "opengl" {
-            return Some(index as u32);
-        }
-    }
-    None
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct TexturedVertex {
+    position: [GLfloat; 3],
+    tex_coords: [GLfloat; 2],
 }
 
-static VERTEX_DATA: [GLfloat; 180] = [
-    -0.5, -0.5, -0.5,  0.0, 0.0,
-     0.5, -0.5, -0.5,  1.0, 0.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-    -0.5,  0.5, -0.5,  0.0, 1.0,
-    -0.5, -0.5, -0.5,  0.0, 0.0,
-
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-     0.5, -0.5,  0.5,  1.0, 0.0,
-     0.5,  0.5,  0.5,  1.0, 1.0,
-     0.5,  0.5,  0.5,  1.0, 1.0,
-    -0.5,  0.5,  0.5,  0.0, 1.0,
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-
-    -0.5,  0.5,  0.5,  1.0, 0.0,
-    -0.5,  0.5, -0.5,  1.0, 1.0,
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-    -0.5,  0.5,  0.5,  1.0, 0.0,
-
-     0.5,  0.5,  0.5,  1.0, 0.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-     0.5, -0.5, -0.5,  0.0, 1.0,
-     0.5, -0.5, -0.5,  0.0, 1.0,
-     0.5, -0.5,  0.5,  0.0, 0.0,
-     0.5,  0.5,  0.5,  1.0, 0.0,
-
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-     0.5, -0.5, -0.5,  1.0, 1.0,
-     0.5, -0.5,  0.5,  1.0, 0.0,
-     0.5, -0.5,  0.5,  1.0, 0.0,
-    -0.5, -0.5,  0.5,  0.0, 0.0,
-    -0.5, -0.5, -0.5,  0.0, 1.0,
-
-    -0.5,  0.5, -0.5,  0.0, 1.0,
-     0.5,  0.5, -0.5,  1.0, 1.0,
-     0.5,  0.5,  0.5,  1.0, 0.0,
-     0.5,  0.5,  0.5,  1.0, 0.0,
-    -0.5,  0.5,  0.5,  0.0, 0.0,
-    -0.5,  0.5, -0.5,  0.0, 1.0
+impl_vertex!(TexturedVertex { position: 0, tex_coords: 2 });
+
+static VERTICES: [TexturedVertex; 36] = [
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 0.0] },
+
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, -0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [-0.5, -0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0] },
+
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [0.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, -0.5], tex_coords: [1.0, 1.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [0.5, 0.5, 0.5], tex_coords: [1.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, 0.5], tex_coords: [0.0, 0.0] },
+    TexturedVertex { position: [-0.5, 0.5, -0.5], tex_coords: [0.0, 1.0] },
 ];
 
 static CUBE_POS: [(f32, f32, f32); 10] = [
@@ -92,165 +85,108 @@ static CUBE_POS: [(f32, f32, f32); 10] = [
   (-1.3,  1.0, -1.5)  
 ];
 
-fn main() {
-    
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-
-    #[cfg(target_os = "macos")]
-    video_subsystem.gl_attr().set_context_profile(GLProfile::Core);
-
-    let window = video_subsystem.window("Window", 800, 600)
-        .resizable()
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
-
-    let canvas = window.into_canvas()
-        .index(find_sdl_gl_driver().unwrap())
-        .present_vsync()
-        .build()
-        .unwrap();
-
-    gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
-    canvas.window().gl_set_context_to_current().unwrap();
-
-    let mut event_pump = sdl_context.event_pump().unwrap();
-
-    let mut vao = 0;
-    let mut vbo = 0;
-    let mut texture1 = 0;
-    let mut texture2 = 0;
-
-    let shader = Shader::from_source("src/examples/coordinate_systems/shader/model_view_projection.glslv", "src/examples/coordinate_systems/shader/model_view_projection.glslf");
-
-    let texture_image1 = image::open(&Path::new("resources/container.jpg")).unwrap();
-    let texture_image2 = image::open(&Path::new("resources/awesomeface.png")).unwrap();
-
-    unsafe {
-        gl::Enable(gl::DEPTH_TEST);  
-
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
-
-        // VAO
-        gl::BindVertexArray(vao);
-
-        // VBO
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, 
-                        ((VERTEX_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                        mem::transmute(&VERTEX_DATA[0]),
-                        gl::STATIC_DRAW);   
-
-        // Position attribute
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<GLfloat>()) as i32, ptr::null());
-        gl::EnableVertexAttribArray(0);
-
-        // Texture attribute
-        gl::VertexAttribPointer(2, 2, gl::FLOAT, gl::FALSE, (5 * mem::size_of::<GLfloat>()) as i32, (3 * mem::size_of::<GLfloat>()) as *const _);
-        gl::EnableVertexAttribArray(2); 
-        
-        gl::BindVertexArray(0);
-
-        // Texture
-        gl::GenTextures(1, &mut texture1);
-        gl::BindTexture(gl::TEXTURE_2D, texture1);
-
-        // Set the texture wrapping parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);	// Set texture wrapping to GL_REPEAT (usually basic wrapping method)
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-
-        // Set texture filtering parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+pub struct MvmMoreCubes {
+    mesh: Mesh,
+    texture1: Texture2D,
+    texture2: Texture2D,
+    shader: Shader,
+    skybox: Skybox,
+    camera: Camera,
+    time: f32,
+}
 
+impl App for MvmMoreCubes {
+    fn init(surface: &dyn Surface) -> MvmMoreCubes {
+        let shader = Shader::from_source("src/examples/coordinate_systems/shader/model_view_projection.glslv", "src/examples/coordinate_systems/shader/model_view_projection.glslf")
+            .unwrap_or_else(|err| panic!("{}", err));
 
-        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, texture_image1.width() as i32, 
-                       texture_image1.height() as i32, 0, gl::RGB, gl::UNSIGNED_BYTE, 
-                       texture_image1.to_rgb().into_raw().as_ptr() as *const c_void);
+        let textures = Texture2D::builder().min_filter(MinFilter::Linear);
+        let texture1 = textures.open("resources/container.jpg")
+            .unwrap_or_else(|err| panic!("{}", err));
+        let texture2 = textures.open("resources/awesomeface.png")
+            .unwrap_or_else(|err| panic!("{}", err));
 
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-        gl::BindTexture(gl::TEXTURE_2D, 0);
+        let mesh = Mesh::new(&VERTICES);
 
-        gl::GenTextures(1, &mut texture2);
-        gl::BindTexture(gl::TEXTURE_2D, texture2);
+        let sky = CubeMap::open_equirectangular("resources/skybox/sky.png")
+            .unwrap_or_else(|err| panic!("{}", err));
+        let skybox = Skybox::new(sky)
+            .unwrap_or_else(|err| panic!("{}", err));
 
-        // Set the texture wrapping parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);	// Set texture wrapping to GL_REPEAT (usually basic wrapping method)
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
+        let mut camera = Camera::new(Vector3::new(0.0, 0.0, 3.0),
+                                     Vector3::new(0.0, 1.0, 0.0),
+                                     0.0, -90.0);
+        camera.set_aspect_ratio(surface.aspect_ratio());
 
-        // Set texture filtering parameters
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+        }
 
-        gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, texture_image2.width() as i32, 
-                       texture_image2.height() as i32, 0, gl::RGB, gl::UNSIGNED_BYTE, 
-                       texture_image2.to_rgb().into_raw().as_ptr() as *const c_void);
+        MvmMoreCubes {
+            mesh,
+            texture1,
+            texture2,
+            shader,
+            skybox,
+            camera,
+            time: 0.0,
+        }
+    }
 
-        gl::GenerateMipmap(gl::TEXTURE_2D);
-        gl::BindTexture(gl::TEXTURE_2D, 0);
+    fn update(&mut self, _surface: &dyn Surface, dt: f32) {
+        self.time += dt;
 
+        // Look from side to side; the sky turns with the view but stays put
+        // in the world
+        let yaw = -90.0 + 30.0 * self.time.sin();
+        self.camera.look(yaw - self.camera.get_yaw(), 0.0);
     }
 
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                _ => {}
-            }
-        }
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
-        
+    fn render(&mut self, _surface: &dyn Surface) {
         unsafe {
             // Clear the screen to black
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
-            gl::ActiveTexture(gl::TEXTURE0);
-            gl::BindTexture(gl::TEXTURE_2D, texture1);
-            gl::Uniform1i(gl::GetUniformLocation(shader.program(), CString::new("ourTexture1").unwrap().as_ptr()), 0);
-
-            gl::ActiveTexture(gl::TEXTURE1);
-            gl::BindTexture(gl::TEXTURE_2D, texture2);
-            gl::Uniform1i(gl::GetUniformLocation(shader.program(), CString::new("ourTexture2").unwrap().as_ptr()), 1);
+            self.shader.gl_use();
 
-            shader.gl_use();
+            self.texture1.bind_to_unit(0);
+            self.shader.set_sampler("ourTexture1", 0);
 
-            let ticks = (timer.ticks() as f32) / 1000.0;
+            self.texture2.bind_to_unit(1);
+            self.shader.set_sampler("ourTexture2", 1);
 
-            let view = Matrix4::from_translation(Vector3::new(0.0, 0.0, -3.0));
-            let projection = perspective(Rad::from(Deg(90.0)), 1.33, 0.1, 100.0);
+            let view = self.camera.get_view();
+            let projection = self.camera.projection();
 
-            let model_loc = gl::GetUniformLocation(shader.program(), CString::new("model").unwrap().as_ptr());
+            self.shader.set_mat4("view", &view);
+            self.shader.set_mat4("projection", &projection);
 
-            let view_loc = gl::GetUniformLocation(shader.program(), CString::new("view").unwrap().as_ptr());
-            gl::UniformMatrix4fv(view_loc, 1, gl::FALSE, view.as_ptr());
-
-            let projection_loc = gl::GetUniformLocation(shader.program(), CString::new("projection").unwrap().as_ptr());
-            gl::UniformMatrix4fv(projection_loc, 1, gl::FALSE, projection.as_ptr());
-
-            gl::BindVertexArray(vao);
-            for i in 0..CUBE_POS.len() {
-                let model = Matrix4::from_translation(Vector3::new(CUBE_POS[i].0, CUBE_POS[i].1, CUBE_POS[i].2)) 
+            for (i, pos) in CUBE_POS.iter().enumerate() {
+                let model = Matrix4::from_translation(Vector3::new(pos.0, pos.1, pos.2)) 
                             * Matrix4::from_angle_x(Deg(20.0 * i as f32))
                             * 0.3 * Matrix4::from_angle_y(Deg(20.0 * i as f32)) * 0.5 * Matrix4::from_angle_z(Deg(20.0 * i as f32));
-                gl::UniformMatrix4fv(model_loc, 1, gl::FALSE, model.as_ptr());
+                self.shader.set_mat4("model", &model);
 
-                gl::DrawArrays(gl::TRIANGLES, 0, 36);
+                self.mesh.draw();
             }
-            gl::BindVertexArray(0);
-        }
 
-        canvas.window().gl_swap_window();
+            // Last, so it is only drawn where no cube is; the skybox drops
+            // the camera's translation itself
+            self.skybox.draw(&view, &projection);
+        }
     }
 
-    // Cleanup
-    unsafe {
-        gl::DeleteVertexArrays(1, &vao);
-        gl::DeleteBuffers(1, &vbo);
+    fn handle_event(&mut self, _window: &Window, event: &Event) -> Flow {
+        self.camera.track_resize(event);
+
+        match *event {
+            Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => Flow::Exit,
+            _ => Flow::Continue,
+        }
     }
-}
\ No newline at end of file
+}
+
+fn main() {
+    app::run::<MvmMoreCubes>(Window::new("Window"));
+}
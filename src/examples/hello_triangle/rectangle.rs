@@ -1,30 +1,16 @@
+extern crate opengl_sdl2_demo;
 extern crate sdl2;
 
-#[allow(non_upper_case_globals)]
-mod gl {
-    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
-}
+use opengl_sdl2_demo::app::{self, App};
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::gl_gen::gl::types::*;
+use opengl_sdl2_demo::window::Window;
 
 use std::ffi::CString;
 use std::mem;
 use std::ptr;
 use std::str;
-use std::time::Duration;
-
-use gl::types::*;
-
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::video::GLProfile;
-
-fn find_sdl_gl_driver() -> Option<u32> {
-    for (index, item) in sdl2::render::drivers().enumerate() {
-        if item.name == "opengl" {
-            return Some(index as u32);
-        }
-    }
-    None
-}
 
 // Vertex data
 static VERTEX_DATA: [GLfloat; 12] = [
@@ -40,7 +26,7 @@ static INDICES_DATA: [GLuint; 6] = [
 ];
 
 // Shader sources
-static VS_SRC: &'static str =
+static VS_SRC: &str =
    "#version 330 core
 
     layout (location = 0) in vec3 position;
@@ -51,7 +37,7 @@ static VS_SRC: &'static str =
     }
     ";
 
-static FS_SRC: &'static str =
+static FS_SRC: &str =
    "#version 330 core
 
     out vec4 color;
@@ -82,11 +68,11 @@ fn compile_shader(src: &str, ty: GLenum) -> GLuint {
             let mut len = 0;
             gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
 
-            let mut buf = Vec::with_capacity(len as usize);
-            buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
+            let mut buf = vec![0u8; len as usize];
             gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
+            buf.pop(); // skip the trailing null character
 
-            panic!("{}", str::from_utf8(&buf).ok().expect("ShaderInfoLog not valid utf8"));
+            panic!("{}", str::from_utf8(&buf).expect("ShaderInfoLog not valid utf8"));
         }
     }
     shader
@@ -109,112 +95,101 @@ fn link_program(vs: GLuint, fs: GLuint) -> GLuint {
             let mut len: GLint = 0;
             gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
 
-            let mut buf = Vec::with_capacity(len as usize);
-            buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
+            let mut buf = vec![0u8; len as usize];
             gl::GetProgramInfoLog(program, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
+            buf.pop(); // skip the trailing null character
             
-            panic!("{}", str::from_utf8(&buf).ok().expect("ProgramInfoLog not valid utf8"));
+            panic!("{}", str::from_utf8(&buf).expect("ProgramInfoLog not valid utf8"));
         }
     } 
     program
 }
 
-fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-
-    #[cfg(target_os = "macos")]
-    video_subsystem.gl_attr().set_context_profile(GLProfile::Core);
-
-    let window = video_subsystem.window("Window", 800, 600)
-        .resizable()
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
-
-    let canvas = window.into_canvas()
-        .index(find_sdl_gl_driver().unwrap())
-        .present_vsync()
-        .build()
-        .unwrap();
-
-    gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
-    canvas.window().gl_set_context_to_current().unwrap();
-
-    let mut event_pump = sdl_context.event_pump().unwrap();
+pub struct Rectangle {
+    vao: GLuint,
+    vbo: GLuint,
+    vs: GLuint,
+    fs: GLuint,
+    program: GLuint,
+}
 
-    let mut vao = 0;
-    let mut vbo = 0;
+impl App for Rectangle {
+    fn init(_surface: &dyn Surface) -> Rectangle {
+        let mut vao = 0;
+        let mut vbo = 0;
 
-    let mut ebo = 0;
+        let mut ebo = 0;
 
-    // Create GLSL shaders
-    let vs = compile_shader(VS_SRC, gl::VERTEX_SHADER);
-    let fs = compile_shader(FS_SRC, gl::FRAGMENT_SHADER);
+        // Create GLSL shaders
+        let vs = compile_shader(VS_SRC, gl::VERTEX_SHADER);
+        let fs = compile_shader(FS_SRC, gl::FRAGMENT_SHADER);
 
-    unsafe {
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
 
-        gl::GenBuffers(1, &mut ebo);
+            gl::GenBuffers(1, &mut ebo);
 
-        // VAO
-        gl::BindVertexArray(vao);
+            // VAO
+            gl::BindVertexArray(vao);
 
-        // VBO
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, 
-                        ((VERTEX_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                        mem::transmute(&VERTEX_DATA[0]),
-                        gl::STATIC_DRAW);
+            // VBO
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(gl::ARRAY_BUFFER, 
+                            (VERTEX_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                            VERTEX_DATA.as_ptr() as *const _,
+                            gl::STATIC_DRAW);
                     
-        // EBO
-        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-        gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, 
-                       ((INDICES_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                       mem::transmute(&INDICES_DATA[0]),
-                       gl::STATIC_DRAW);
+            // EBO
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, 
+                           (INDICES_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                           INDICES_DATA.as_ptr() as *const _,
+                           gl::STATIC_DRAW);
 
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
-        gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
+            gl::EnableVertexAttribArray(0);
 
-        gl::BindVertexArray(0);
-    }
+            gl::BindVertexArray(0);
+        }
 
-    let program = link_program(vs, fs);
+        let program = link_program(vs, fs);
 
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                _ => {}
-            }
+        Rectangle {
+            vao,
+            vbo,
+            vs,
+            fs,
+            program,
         }
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
-        
+    }
+
+    fn render(&mut self, _surface: &dyn Surface) {
         unsafe {
             // Clear the screen to black
             gl::ClearColor(0.3, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
 
-            gl::UseProgram(program);
-            gl::BindVertexArray(vao);
+            gl::UseProgram(self.program);
+            gl::BindVertexArray(self.vao);
             gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, ptr::null());
             gl::BindVertexArray(0);
         }
-
-        canvas.window().gl_swap_window();
     }
+}
 
-    // Cleanup
-    unsafe {
-        gl::DeleteProgram(program);
-        gl::DeleteShader(fs);
-        gl::DeleteShader(vs);
-        gl::DeleteBuffers(1, &vbo);
-        gl::DeleteVertexArrays(1, &vao);
+impl Drop for Rectangle {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteShader(self.fs);
+            gl::DeleteShader(self.vs);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
     }
-}
\ No newline at end of file
+}
+
+fn main() {
+    app::run::<Rectangle>(Window::new("Window"));
+}
@@ -1,30 +1,16 @@
+extern crate opengl_sdl2_demo;
 extern crate sdl2;
 
-#[allow(non_upper_case_globals)]
-mod gl {
-    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
-}
+use opengl_sdl2_demo::app::{self, App};
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::gl_gen::gl::types::*;
+use opengl_sdl2_demo::window::Window;
 
 use std::ffi::CString;
 use std::mem;
 use std::ptr;
 use std::str;
-use std::time::Duration;
-
-use gl::types::*;
-
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::video::GLProfile;
-
-fn find_sdl_gl_driver() -> Option<u32> {
-    for (index, item) in sdl2::render::drivers().enumerate() {
-        if item.name == "opengl" {
-            return Some(index as u32);
-        }
-    }
-    None
-}
 
 // Vertex data for first triangle
 static VERTEX_DATA_1: [GLfloat; 9] = [
@@ -41,7 +27,7 @@ static VERTEX_DATA_2: [GLfloat; 9] = [
 ];
 
 // Shader sources
-static VS_SRC_1: &'static str =
+static VS_SRC_1: &str =
    "#version 330 core
 
     layout (location = 0) in vec3 position;
@@ -53,7 +39,7 @@ static VS_SRC_1: &'static str =
     ";
 
 // Shader sources
-static VS_SRC_2: &'static str =
+static VS_SRC_2: &str =
    "#version 330 core
 
     layout (location = 0) in vec3 position;
@@ -64,7 +50,7 @@ static VS_SRC_2: &'static str =
     }
     ";
 
-static FS_SRC_1: &'static str =
+static FS_SRC_1: &str =
    "#version 330 core
 
     out vec4 color;
@@ -75,7 +61,7 @@ static FS_SRC_1: &'static str =
     }
     ";
 
-static FS_SRC_2: &'static str =
+static FS_SRC_2: &str =
    "#version 330 core
 
     out vec4 color;
@@ -106,11 +92,11 @@ fn compile_shader(src: &str, ty: GLenum) -> GLuint {
             let mut len = 0;
             gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
 
-            let mut buf = Vec::with_capacity(len as usize);
-            buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
+            let mut buf = vec![0u8; len as usize];
             gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
+            buf.pop(); // skip the trailing null character
 
-            panic!("{}", str::from_utf8(&buf).ok().expect("ShaderInfoLog not valid utf8"));
+            panic!("{}", str::from_utf8(&buf).expect("ShaderInfoLog not valid utf8"));
         }
     }
     shader
@@ -133,132 +119,119 @@ fn link_program(vs: GLuint, fs: GLuint) -> GLuint {
             let mut len: GLint = 0;
             gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
 
-            let mut buf = Vec::with_capacity(len as usize);
-            buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
+            let mut buf = vec![0u8; len as usize];
             gl::GetProgramInfoLog(program, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
+            buf.pop(); // skip the trailing null character
             
-            panic!("{}", str::from_utf8(&buf).ok().expect("ProgramInfoLog not valid utf8"));
+            panic!("{}", str::from_utf8(&buf).expect("ProgramInfoLog not valid utf8"));
         }
     } 
     program
 }
 
-fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-
-    #[cfg(target_os = "macos")]
-    video_subsystem.gl_attr().set_context_profile(GLProfile::Core);
-
-    let window = video_subsystem.window("Window", 800, 600)
-        .resizable()
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
-
-    let canvas = window.into_canvas()
-        .index(find_sdl_gl_driver().unwrap())
-        .present_vsync()
-        .build()
-        .unwrap();
-
-    gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
-    canvas.window().gl_set_context_to_current().unwrap();
+pub struct TwoTrianglesWithShaders {
+    vao: [GLuint; 2],
+    vbo: [GLuint; 2],
+    program_1: GLuint,
+    program_2: GLuint,
+}
 
-    let mut event_pump = sdl_context.event_pump().unwrap();
+impl App for TwoTrianglesWithShaders {
+    fn init(_surface: &dyn Surface) -> TwoTrianglesWithShaders {
+        let mut vao = [0; 2];
+        let mut vbo = [0; 2];
 
-    let mut vao = [0; 2];
-    let mut vbo = [0; 2];
+        // Create GLSL shaders
+        let vs_1 = compile_shader(VS_SRC_1, gl::VERTEX_SHADER);
+        let fs_1 = compile_shader(FS_SRC_1, gl::FRAGMENT_SHADER);
 
-    // Create GLSL shaders
-    let vs_1 = compile_shader(VS_SRC_1, gl::VERTEX_SHADER);
-    let fs_1 = compile_shader(FS_SRC_1, gl::FRAGMENT_SHADER);
+        let vs_2 = compile_shader(VS_SRC_2, gl::VERTEX_SHADER);
+        let fs_2 = compile_shader(FS_SRC_2, gl::FRAGMENT_SHADER);
 
-    let vs_2 = compile_shader(VS_SRC_2, gl::VERTEX_SHADER);
-    let fs_2 = compile_shader(FS_SRC_2, gl::FRAGMENT_SHADER);
+        unsafe {
+            gl::GenVertexArrays(2, &mut vao[0]);
+            gl::GenBuffers(2, &mut vbo[0]);
 
-    unsafe {
-        gl::GenVertexArrays(2, &mut vao[0]);
-        gl::GenBuffers(2, &mut vbo[0]);
+            // Bind first triangle
+            gl::BindVertexArray(vao[0]);
 
-        // Bind first triangle
-        gl::BindVertexArray(vao[0]);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo[0]);
+            gl::BufferData(gl::ARRAY_BUFFER, 
+                            (VERTEX_DATA_1.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                            VERTEX_DATA_1.as_ptr() as *const _,
+                            gl::STATIC_DRAW);
 
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo[0]);
-        gl::BufferData(gl::ARRAY_BUFFER, 
-                        ((VERTEX_DATA_1.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                        mem::transmute(&VERTEX_DATA_1[0]),
-                        gl::STATIC_DRAW);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
+            gl::EnableVertexAttribArray(0);
 
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
-        gl::EnableVertexAttribArray(0);
+            gl::BindVertexArray(0);
 
-        gl::BindVertexArray(0);
+            // Bind second triangle
+            gl::BindVertexArray(vao[1]);
 
-        // Bind second triangle
-        gl::BindVertexArray(vao[1]);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo[1]);
+            gl::BufferData(gl::ARRAY_BUFFER, 
+                            (VERTEX_DATA_2.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                            VERTEX_DATA_2.as_ptr() as *const _,
+                            gl::STATIC_DRAW);
 
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo[1]);
-        gl::BufferData(gl::ARRAY_BUFFER, 
-                        ((VERTEX_DATA_2.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                        mem::transmute(&VERTEX_DATA_2[0]),
-                        gl::STATIC_DRAW);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
+            gl::EnableVertexAttribArray(0);
 
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
-        gl::EnableVertexAttribArray(0);
+            gl::BindVertexArray(0);
 
-        gl::BindVertexArray(0);
+        }
 
-    }
+        let program_1 = link_program(vs_1, fs_1);
+        let program_2 = link_program(vs_2, fs_2);
 
-    let program_1 = link_program(vs_1, fs_1);
-    let program_2 = link_program(vs_2, fs_2);
+        unsafe {
+            gl::DeleteShader(fs_1);
+            gl::DeleteShader(vs_1);
+            gl::DeleteShader(fs_2);
+            gl::DeleteShader(vs_2);
+        }
 
-    unsafe {
-        gl::DeleteShader(fs_1);
-        gl::DeleteShader(vs_1);
-        gl::DeleteShader(fs_2);
-        gl::DeleteShader(vs_2);
+        TwoTrianglesWithShaders {
+            vao,
+            vbo,
+            program_1,
+            program_2,
+        }
     }
 
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                _ => {}
-            }
-        }
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
-        
+    fn render(&mut self, _surface: &dyn Surface) {
         unsafe {
             // Clear the screen to black
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
 
-            gl::UseProgram(program_1);
+            gl::UseProgram(self.program_1);
 
-            gl::BindVertexArray(vao[0]);
+            gl::BindVertexArray(self.vao[0]);
             gl::DrawArrays(gl::TRIANGLES, 0, 3);
             gl::BindVertexArray(0);
 
-            gl::UseProgram(program_2);
+            gl::UseProgram(self.program_2);
 
-            gl::BindVertexArray(vao[1]);
+            gl::BindVertexArray(self.vao[1]);
             gl::DrawArrays(gl::TRIANGLES, 0, 3);
             gl::BindVertexArray(0);
         }
-
-        canvas.window().gl_swap_window();
     }
+}
 
-    // Cleanup
-    unsafe {
-        gl::DeleteProgram(program_1);
-        gl::DeleteProgram(program_2);
-        gl::DeleteBuffers(2, &vbo[0]);
-        gl::DeleteVertexArrays(2, &vao[0]);
+impl Drop for TwoTrianglesWithShaders {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.program_1);
+            gl::DeleteProgram(self.program_2);
+            gl::DeleteBuffers(2, &self.vbo[0]);
+            gl::DeleteVertexArrays(2, &self.vao[0]);
+        }
     }
-}
\ No newline at end of file
+}
+
+fn main() {
+    app::run::<TwoTrianglesWithShaders>(Window::new("Window"));
+}
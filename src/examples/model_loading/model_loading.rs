@@ -0,0 +1,171 @@
+extern crate cgmath;
+extern crate opengl_sdl2_demo;
+extern crate sdl2;
+
+use opengl_sdl2_demo::app::{self, App, Flow};
+use opengl_sdl2_demo::camera::{Camera, CameraMovement, OrbitCamera, ViewCamera};
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::input::{Bindings, InputState};
+use opengl_sdl2_demo::model::Model;
+use opengl_sdl2_demo::shader::Shader;
+use opengl_sdl2_demo::window::Window;
+
+use cgmath::{Deg, Matrix4, Vector3};
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CameraMode {
+    Fly,
+    Orbit,
+}
+
+pub struct ModelLoading {
+    model: Model,
+    shader: Shader,
+    /// Tab switches between the cameras; each keeps its own state.
+    mode: CameraMode,
+    camera: Camera,
+    orbit: OrbitCamera,
+    bindings: Bindings,
+    time: f32,
+}
+
+impl App for ModelLoading {
+    fn init(surface: &dyn Surface) -> ModelLoading {
+        let shader = Shader::from_source("src/examples/model_loading/shader/model.glslv", "src/examples/model_loading/shader/model.glslf")
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        let model = Model::load("resources/models/crate.obj")
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        let mut camera = Camera::new(Vector3::new(0.0, 0.5, 3.0),
+                                     Vector3::new(0.0, 1.0, 0.0),
+                                     0.0, -90.0);
+        camera.set_aspect_ratio(surface.aspect_ratio());
+
+        let mut orbit = OrbitCamera::new(Vector3::new(0.0, 0.0, 0.0), 3.0, 0.0, 20.0);
+        orbit.set_aspect_ratio(surface.aspect_ratio());
+        if let Some(bounds) = model.bounds() {
+            orbit.zoom_to_fit(&bounds);
+        }
+
+        let bindings = Bindings::load("resources/bindings/camera.bindings")
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+        }
+
+        ModelLoading {
+            model,
+            shader,
+            mode: CameraMode::Orbit,
+            camera,
+            orbit,
+            bindings,
+            time: 0.0,
+        }
+    }
+
+    /// In fly mode the bound keys move for as long as they are held, and the
+    /// mouse wheel zooms.
+    fn handle_input(&mut self, _window: &Window, input: &InputState, dt: f32) {
+        if self.mode != CameraMode::Fly {
+            return;
+        }
+
+        let actions = input.actions(&self.bindings);
+        self.camera.move_position(CameraMovement::FORWARD, actions.value("move_forward") * dt);
+        self.camera.move_position(CameraMovement::RIGHT, actions.value("move_right") * dt);
+        self.camera.move_position(CameraMovement::UP, actions.value("move_up") * dt);
+        self.camera.zoom_by(actions.value("zoom"));
+    }
+
+    fn update(&mut self, _surface: &dyn Surface, dt: f32) {
+        self.time += dt;
+    }
+
+    fn render(&mut self, _surface: &dyn Surface) {
+        unsafe {
+            gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        self.shader.gl_use();
+        self.shader.set_sampler("diffuseTexture", 0);
+        self.shader.set_vec3("lightDirection", Vector3::new(-0.4, -1.0, -0.6));
+
+        let model = Matrix4::from_angle_y(Deg(30.0 * self.time));
+        let camera: &dyn ViewCamera = match self.mode {
+            CameraMode::Fly => &self.camera,
+            CameraMode::Orbit => &self.orbit,
+        };
+        let view = camera.get_view();
+        let projection = camera.projection();
+
+        self.shader.set_mat4("model", &model);
+        self.shader.set_mat4("view", &view);
+        self.shader.set_mat4("projection", &projection);
+
+        self.model.draw();
+    }
+
+    fn handle_event(&mut self, _window: &Window, event: &Event) -> Flow {
+        self.camera.track_resize(event);
+        self.orbit.track_resize(event);
+
+        match *event {
+            Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => return Flow::Exit,
+            Event::KeyDown{keycode: Some(Keycode::Tab), ..} => {
+                self.mode = match self.mode {
+                    CameraMode::Fly => CameraMode::Orbit,
+                    CameraMode::Orbit => CameraMode::Fly,
+                };
+            },
+            _ => {},
+        }
+
+        if self.mode == CameraMode::Orbit {
+            self.orbit(event);
+        }
+        Flow::Continue
+    }
+}
+
+impl ModelLoading {
+    /// Left drag orbits, right or middle drag pans, the mouse wheel dollies
+    /// and F frames the whole model.
+    fn orbit(&mut self, event: &Event) {
+        match *event {
+            Event::MouseMotion{mousestate, xrel, yrel, ..} => {
+                // Drag the model along with the mouse
+                if mousestate.left() {
+                    self.orbit.rotate(-xrel as f32, yrel as f32);
+                } else if mousestate.right() || mousestate.middle() {
+                    self.orbit.pan(-xrel as f32, yrel as f32);
+                }
+            },
+            Event::MouseWheel{y, ..} => {
+                self.orbit.dolly(y as f32);
+            },
+            Event::KeyDown{keycode: Some(Keycode::F), ..} => {
+                if let Some(bounds) = self.model.bounds() {
+                    self.orbit.zoom_to_fit(&bounds);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+fn main() {
+    let window = Window::builder("Model loading")
+        .msaa(4)
+        .build()
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    app::run::<ModelLoading>(window);
+}
@@ -0,0 +1,143 @@
+extern crate cgmath;
+extern crate opengl_sdl2_demo;
+extern crate sdl2;
+
+use opengl_sdl2_demo::app::{self, App, Flow};
+use opengl_sdl2_demo::camera::{Camera, CameraMovement};
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::scene::{CameraProjection, Scene};
+use opengl_sdl2_demo::shader::Shader;
+use opengl_sdl2_demo::window::Window;
+
+use std::env;
+use std::sync::OnceLock;
+
+use cgmath::{Matrix4, SquareMatrix, Vector3};
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+const DEFAULT_SCENE: &str = "resources/models/crate.gltf";
+
+// Set from the command line before the app starts
+static SCENE_PATH: OnceLock<String> = OnceLock::new();
+
+pub struct SceneViewer {
+    scene: Scene,
+    shader: Shader,
+    /// The view and projection of the scene's own camera, used until the
+    /// free camera is moved.
+    scene_camera: Option<(Matrix4<f32>, CameraProjection)>,
+    camera: Camera,
+    delta_tick: f32,
+}
+
+impl App for SceneViewer {
+    fn init(surface: &dyn Surface) -> SceneViewer {
+        let shader = Shader::from_source("src/examples/model_loading/shader/model.glslv", "src/examples/model_loading/shader/scene.glslf")
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        let path = SCENE_PATH.get().map_or(DEFAULT_SCENE, String::as_str);
+        let scene = Scene::load(path)
+            .unwrap_or_else(|err| panic!("{}: {}", path, err));
+
+        let scene_camera = scene.camera_node()
+            .and_then(|(camera, world)| world.invert().map(|view| (view, camera.projection)));
+
+        // Start the free camera where the scene camera is
+        let mut camera = match scene.camera_node() {
+            Some((_, world)) => Camera::with_direction(world.w.truncate(),
+                                                       -world.z.truncate(),
+                                                       Vector3::new(0.0, 1.0, 0.0)),
+            None => Camera::new(Vector3::new(0.0, 0.0, 3.0),
+                                Vector3::new(0.0, 1.0, 0.0),
+                                0.0, -90.0),
+        };
+        camera.set_aspect_ratio(surface.aspect_ratio());
+
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+        }
+
+        SceneViewer {
+            scene,
+            shader,
+            scene_camera,
+            camera,
+            delta_tick: 0.0,
+        }
+    }
+
+    fn update(&mut self, _surface: &dyn Surface, dt: f32) {
+        self.delta_tick = dt;
+    }
+
+    fn render(&mut self, surface: &dyn Surface) {
+        unsafe {
+            gl::ClearColor(0.2, 0.3, 0.3, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        let (view, projection) = match self.scene_camera {
+            Some((view, ref projection)) => (view, projection.matrix(surface.aspect_ratio())),
+            None => (self.camera.get_view(), self.camera.projection()),
+        };
+
+        let shader = &self.shader;
+        shader.gl_use();
+        shader.set_mat4("view", &view);
+        shader.set_mat4("projection", &projection);
+        shader.set_vec3("lightDirection", Vector3::new(-0.4, -1.0, -0.6));
+        shader.set_sampler("baseColorTexture", 0);
+
+        let textures = self.scene.textures();
+        self.scene.draw_with(|model, material| {
+            shader.set_mat4("model", model);
+
+            shader.set_vec4("baseColorFactor", material.base_color_factor);
+            shader.set_bool("hasBaseColorTexture", material.base_color_texture.is_some());
+            if let Some(texture) = material.base_color_texture {
+                textures[texture.texture].bind_to_unit(0);
+            }
+        });
+    }
+
+    fn handle_event(&mut self, _window: &Window, event: &Event) -> Flow {
+        self.camera.track_resize(event);
+
+        match *event {
+            Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => return Flow::Exit,
+            Event::KeyDown{keycode: Some(key), ..} => {
+                let movement = match key {
+                    Keycode::W => CameraMovement::FORWARD,
+                    Keycode::A => CameraMovement::LEFT,
+                    Keycode::S => CameraMovement::BACKWARD,
+                    Keycode::D => CameraMovement::RIGHT,
+                    _ => return Flow::Continue,
+                };
+                self.scene_camera = None;
+                self.camera.move_position(movement, self.delta_tick);
+            },
+            Event::MouseWheel{y, ..} => {
+                self.scene_camera = None;
+                self.camera.zoom_by(y as f32);
+            }
+            _ => {},
+        }
+        Flow::Continue
+    }
+}
+
+fn main() {
+    if let Some(path) = env::args().nth(1) {
+        SCENE_PATH.set(path).unwrap();
+    }
+
+    let window = Window::builder("Scene viewer")
+        .msaa(4)
+        .build()
+        .unwrap_or_else(|err| panic!("{}", err));
+
+    app::run::<SceneViewer>(window);
+}
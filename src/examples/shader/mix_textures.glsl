@@ -0,0 +1,7 @@
+uniform sampler2D ourTexture1;
+uniform sampler2D ourTexture2;
+
+vec4 mix_textures(vec2 texCoord)
+{
+    return mix(texture(ourTexture1, texCoord), texture(ourTexture2, vec2(1.0 - texCoord.x, texCoord.y)), 0.2);
+}
@@ -1,121 +1,62 @@
+extern crate opengl_sdl2_demo;
 extern crate sdl2;
 
-#[path="../../shader/mod.rs"]
-mod shader;
-use shader::Shader;
-
-#[path="../../gl_gen/mod.rs"]
-mod gl_gen;
-use gl_gen::gl;
-use gl_gen::gl::types::*;
-
-use std::mem;
-use std::ptr;
-use std::time::Duration;
-
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::video::GLProfile;
-
-fn find_sdl_gl_driver() -> Option<u32> {
-    for (index, item) in sdl2::render::drivers().enumerate() {
-        if item.name == "opengl" {
-            return Some(index as u32);
-        }
-    }
-    None
+use opengl_sdl2_demo::app::{self, App};
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::gl_gen::gl::types::*;
+use opengl_sdl2_demo::impl_vertex;
+use opengl_sdl2_demo::mesh::Mesh;
+use opengl_sdl2_demo::shader::Shader;
+use opengl_sdl2_demo::window::Window;
+
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct ColoredVertex {
+    position: [GLfloat; 3],
+    color: [GLfloat; 3],
 }
 
+impl_vertex!(ColoredVertex { position: 0, color: 1 });
+
 // Vertex data
-static VERTEX_DATA: [GLfloat; 18] = [
-        // Position       // Color
-        -0.5, -0.5, 0.0,  1.0, 0.0, 0.0,
-         0.5, -0.5, 0.0,  0.0, 1.0, 0.0,
-         0.0,  0.5, 0.0,  0.0, 0.0, 1.0,
+static VERTICES: [ColoredVertex; 3] = [
+    ColoredVertex { position: [-0.5, -0.5, 0.0], color: [1.0, 0.0, 0.0] },
+    ColoredVertex { position: [0.5, -0.5, 0.0], color: [0.0, 1.0, 0.0] },
+    ColoredVertex { position: [0.0, 0.5, 0.0], color: [0.0, 0.0, 1.0] },
 ];
 
-fn main() {
-    
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-
-    #[cfg(target_os = "macos")]
-    video_subsystem.gl_attr().set_context_profile(GLProfile::Core);
-
-    let window = video_subsystem.window("Window", 800, 600)
-        .resizable()
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
-
-    let canvas = window.into_canvas()
-        .index(find_sdl_gl_driver().unwrap())
-        .present_vsync()
-        .build()
-        .unwrap();
-
-    gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
-    canvas.window().gl_set_context_to_current().unwrap();
-
-    let mut event_pump = sdl_context.event_pump().unwrap();
-
-    let mut vao = 0;
-    let mut vbo = 0;
-
-    let shader = Shader::from_source("src/examples/shaders/shader/triangle-3.glslv", "src/examples/shaders/shader/triangle-3.glslf");
-
-    unsafe {
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
-
-        // VAO
-        gl::BindVertexArray(vao);
-
-        // VBO
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, 
-                        ((VERTEX_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                        mem::transmute(&VERTEX_DATA[0]),
-                        gl::STATIC_DRAW);
+pub struct TriangleExercise3 {
+    mesh: Mesh,
+    shader: Shader,
+}
 
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (6 * mem::size_of::<GLfloat>()) as i32, ptr::null());
-        gl::EnableVertexAttribArray(0);
+impl App for TriangleExercise3 {
+    fn init(_surface: &dyn Surface) -> TriangleExercise3 {
+        let shader = Shader::from_source("src/examples/shaders/shader/triangle-3.glslv", "src/examples/shaders/shader/triangle-3.glslf")
+            .unwrap_or_else(|err| panic!("{}", err));
 
-        gl::VertexAttribPointer(1, 3, gl::FLOAT, gl::FALSE, (6 * mem::size_of::<GLfloat>()) as i32, (3 * mem::size_of::<GLfloat>()) as *const _);
-        gl::EnableVertexAttribArray(1);
+        let mesh = Mesh::new(&VERTICES);
 
-        gl::BindVertexArray(0);
+        TriangleExercise3 {
+            mesh,
+            shader,
+        }
     }
 
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                _ => {}
-            }
-        }
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
-        
+    fn render(&mut self, _surface: &dyn Surface) {
         unsafe {
             // Clear the screen to black
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
 
-            shader.gl_use();
-            gl::BindVertexArray(vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, 6);
-            gl::BindVertexArray(0);
+            self.shader.gl_use();
+            self.mesh.draw();
         }
-
-        canvas.window().gl_swap_window();
     }
+}
 
-    // Cleanup
-    unsafe {
-        gl::DeleteBuffers(1, &vbo);
-        gl::DeleteVertexArrays(1, &vao);
-    }
-}
\ No newline at end of file
+fn main() {
+    app::run::<TriangleExercise3>(Window::new("Window"));
+}
@@ -1,30 +1,17 @@
+extern crate opengl_sdl2_demo;
 extern crate sdl2;
 
-#[allow(non_upper_case_globals)]
-mod gl {
-    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
-}
+use opengl_sdl2_demo::app::{self, App};
+use opengl_sdl2_demo::context::Surface;
+use opengl_sdl2_demo::gl_gen::gl;
+use opengl_sdl2_demo::gl_gen::gl::types::*;
+use opengl_sdl2_demo::shader::UniformCache;
+use opengl_sdl2_demo::window::Window;
 
 use std::ffi::CString;
 use std::mem;
 use std::ptr;
 use std::str;
-use std::time::Duration;
-
-use gl::types::*;
-
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::video::GLProfile;
-
-fn find_sdl_gl_driver() -> Option<u32> {
-    for (index, item) in sdl2::render::drivers().enumerate() {
-        if item.name == "opengl" {
-            return Some(index as u32);
-        }
-    }
-    None
-}
 
 // Vertex data
 static VERTEX_DATA: [GLfloat; 9] = [
@@ -34,7 +21,7 @@ static VERTEX_DATA: [GLfloat; 9] = [
 ];
 
 // Shader sources
-static VS_SRC: &'static str =
+static VS_SRC: &str =
    "#version 330 core
 
     layout (location = 0) in vec3 position;
@@ -45,7 +32,7 @@ static VS_SRC: &'static str =
     }
     ";
 
-static FS_SRC: &'static str =
+static FS_SRC: &str =
    "#version 330 core
 
     out vec4 color;
@@ -78,11 +65,11 @@ fn compile_shader(src: &str, ty: GLenum) -> GLuint {
             let mut len = 0;
             gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
 
-            let mut buf = Vec::with_capacity(len as usize);
-            buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
+            let mut buf = vec![0u8; len as usize];
             gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
+            buf.pop(); // skip the trailing null character
 
-            panic!("{}", str::from_utf8(&buf).ok().expect("ShaderInfoLog not valid utf8"));
+            panic!("{}", str::from_utf8(&buf).expect("ShaderInfoLog not valid utf8"));
         }
     }
     shader
@@ -105,110 +92,103 @@ fn link_program(vs: GLuint, fs: GLuint) -> GLuint {
             let mut len: GLint = 0;
             gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
 
-            let mut buf = Vec::with_capacity(len as usize);
-            buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
+            let mut buf = vec![0u8; len as usize];
             gl::GetProgramInfoLog(program, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
+            buf.pop(); // skip the trailing null character
             
-            panic!("{}", str::from_utf8(&buf).ok().expect("ProgramInfoLog not valid utf8"));
+            panic!("{}", str::from_utf8(&buf).expect("ProgramInfoLog not valid utf8"));
         }
     } 
     program
 }
 
-fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let mut timer = sdl_context.timer().unwrap();
-
-
-    #[cfg(target_os = "macos")]
-    video_subsystem.gl_attr().set_context_profile(GLProfile::Core);
+pub struct Uniform {
+    vao: GLuint,
+    vbo: GLuint,
+    program: GLuint,
+    uniforms: UniformCache,
+    time: f32,
+}
 
-    let window = video_subsystem.window("Window", 800, 600)
-        .resizable()
-        .position_centered()
-        .opengl()
-        .build()
-        .unwrap();
+impl App for Uniform {
+    fn init(_surface: &dyn Surface) -> Uniform {
+        let mut vao = 0;
+        let mut vbo = 0;
 
-    let canvas = window.into_canvas()
-        .index(find_sdl_gl_driver().unwrap())
-        .present_vsync()
-        .build()
-        .unwrap();
+        // Create GLSL shaders
+        let vs = compile_shader(VS_SRC, gl::VERTEX_SHADER);
+        let fs = compile_shader(FS_SRC, gl::FRAGMENT_SHADER);
 
-    gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
-    canvas.window().gl_set_context_to_current().unwrap();
+        unsafe {
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut vbo);
 
-    let mut event_pump = sdl_context.event_pump().unwrap();
+            // VAO
+            gl::BindVertexArray(vao);
 
-    let mut vao = 0;
-    let mut vbo = 0;
+            // VBO
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(gl::ARRAY_BUFFER, 
+                            (VERTEX_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                            VERTEX_DATA.as_ptr() as *const _,
+                            gl::STATIC_DRAW);
 
-    // Create GLSL shaders
-    let vs = compile_shader(VS_SRC, gl::VERTEX_SHADER);
-    let fs = compile_shader(FS_SRC, gl::FRAGMENT_SHADER);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
+            gl::EnableVertexAttribArray(0);
 
-    unsafe {
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
+            gl::BindVertexArray(0);
+        }
 
-        // VAO
-        gl::BindVertexArray(vao);
+        let program = link_program(vs, fs);
 
-        // VBO
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(gl::ARRAY_BUFFER, 
-                        ((VERTEX_DATA.len() * mem::size_of::<GLfloat>()) as GLsizeiptr),
-                        mem::transmute(&VERTEX_DATA[0]),
-                        gl::STATIC_DRAW);
+        unsafe {
+            gl::DeleteShader(fs);
+            gl::DeleteShader(vs);
+        }
 
-        gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 0, ptr::null());
-        gl::EnableVertexAttribArray(0);
+        let uniforms = UniformCache::new();
 
-        gl::BindVertexArray(0);
+        Uniform {
+            vao,
+            vbo,
+            program,
+            uniforms,
+            time: 0.0,
+        }
     }
 
-    let program = link_program(vs, fs);
-
-    unsafe {
-        gl::DeleteShader(fs);
-        gl::DeleteShader(vs);
+    fn update(&mut self, _surface: &dyn Surface, dt: f32) {
+        self.time += dt;
     }
 
-    'running: loop {
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
-                    break 'running
-                },
-                _ => {}
-            }
-        }
-        ::std::thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
-        
+    fn render(&mut self, _surface: &dyn Surface) {
         unsafe {
             // Clear the screen to black
             gl::ClearColor(0.2, 0.3, 0.3, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT);
 
-            gl::UseProgram(program);
+            gl::UseProgram(self.program);
 
-            let vertex_color_location = gl::GetUniformLocation(program, "ourColor".as_ptr() as *const i8);
-            gl::Uniform4f(vertex_color_location, 0.0, ((timer.ticks() % 1500) as f32) / 1500.0, 0.0, 1.0);
+            let vertex_color_location = self.uniforms.location(self.program, "ourColor");
+            gl::Uniform4f(vertex_color_location, 0.0, (self.time % 1.5) / 1.5, 0.0, 1.0);
 
-            gl::BindVertexArray(vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, 6);
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
             gl::BindVertexArray(0);
         }
-
-        canvas.window().gl_swap_window();
     }
+}
 
-    // Cleanup
-    unsafe {
-        gl::DeleteProgram(program);
-        gl::DeleteBuffers(1, &vbo);
-        gl::DeleteVertexArrays(1, &vao);
+impl Drop for Uniform {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.program);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
     }
-}
\ No newline at end of file
+}
+
+fn main() {
+    app::run::<Uniform>(Window::new("Window"));
+}
@@ -1,4 +1,4 @@
-#[allow(non_upper_case_globals)]
+#[allow(non_upper_case_globals, bare_trait_objects, unused_mut, clippy::all)]
 pub mod gl {
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
-}
\ No newline at end of file
+}
@@ -0,0 +1,117 @@
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+use libc;
+
+use super::HeadlessError;
+
+pub type EGLDisplay = *mut c_void;
+pub type EGLConfig = *mut c_void;
+pub type EGLContext = *mut c_void;
+pub type EGLSurface = *mut c_void;
+pub type EGLint = i32;
+pub type EGLenum = u32;
+pub type EGLBoolean = u32;
+
+pub const NONE: EGLint = 0x3038;
+pub const SURFACE_TYPE: EGLint = 0x3033;
+pub const PBUFFER_BIT: EGLint = 0x0001;
+pub const RENDERABLE_TYPE: EGLint = 0x3040;
+pub const OPENGL_BIT: EGLint = 0x0008;
+pub const OPENGL_API: EGLenum = 0x30A2;
+pub const CONTEXT_MAJOR_VERSION: EGLint = 0x3098;
+pub const CONTEXT_MINOR_VERSION: EGLint = 0x30FB;
+pub const CONTEXT_OPENGL_PROFILE_MASK: EGLint = 0x30FD;
+pub const CONTEXT_OPENGL_CORE_PROFILE_BIT: EGLint = 0x0001;
+pub const PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
+
+pub const NO_CONTEXT: EGLContext = 0 as EGLContext;
+pub const NO_SURFACE: EGLSurface = 0 as EGLSurface;
+pub const NO_DISPLAY: EGLDisplay = 0 as EGLDisplay;
+
+/// The handful of EGL entry points needed for a surfaceless context, loaded
+/// from `libEGL.so.1` at runtime so nothing has to be linked at build time.
+pub struct Egl {
+    library: *mut libc::c_void,
+    pub get_proc_address: extern "C" fn(*const c_char) -> *const c_void,
+    pub get_display: extern "C" fn(*mut c_void) -> EGLDisplay,
+    pub initialize: extern "C" fn(EGLDisplay, *mut EGLint, *mut EGLint) -> EGLBoolean,
+    pub bind_api: extern "C" fn(EGLenum) -> EGLBoolean,
+    pub choose_config: extern "C" fn(EGLDisplay, *const EGLint, *mut EGLConfig, EGLint, *mut EGLint) -> EGLBoolean,
+    pub create_context: extern "C" fn(EGLDisplay, EGLConfig, EGLContext, *const EGLint) -> EGLContext,
+    pub make_current: extern "C" fn(EGLDisplay, EGLSurface, EGLSurface, EGLContext) -> EGLBoolean,
+    pub destroy_context: extern "C" fn(EGLDisplay, EGLContext) -> EGLBoolean,
+    pub get_error: extern "C" fn() -> EGLint,
+}
+
+impl Egl {
+    pub fn load() -> Result<Egl, HeadlessError> {
+        let name = CString::new("libEGL.so.1").unwrap();
+        let library = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if library.is_null() {
+            return Err(HeadlessError::Load("libEGL.so.1 could not be opened".to_string()));
+        }
+
+        unsafe {
+            Ok(Egl {
+                library,
+                get_proc_address: symbol(library, "eglGetProcAddress")?,
+                get_display: symbol(library, "eglGetDisplay")?,
+                initialize: symbol(library, "eglInitialize")?,
+                bind_api: symbol(library, "eglBindAPI")?,
+                choose_config: symbol(library, "eglChooseConfig")?,
+                create_context: symbol(library, "eglCreateContext")?,
+                make_current: symbol(library, "eglMakeCurrent")?,
+                destroy_context: symbol(library, "eglDestroyContext")?,
+                get_error: symbol(library, "eglGetError")?,
+            })
+        }
+    }
+
+    /// Looks up an EGL extension or GL function.
+    pub fn proc_address(&self, name: &str) -> *const c_void {
+        let name = CString::new(name).unwrap();
+        (self.get_proc_address)(name.as_ptr())
+    }
+
+    /// The display of Mesa's surfaceless platform, or the default display on
+    /// implementations without it.
+    pub fn surfaceless_display(&self) -> EGLDisplay {
+        let get_platform_display = self.proc_address("eglGetPlatformDisplayEXT");
+        if !get_platform_display.is_null() {
+            let get_platform_display: extern "C" fn(EGLenum, *mut c_void, *const EGLint) -> EGLDisplay =
+                unsafe { mem::transmute::<*const c_void, _>(get_platform_display) };
+            let display = get_platform_display(PLATFORM_SURFACELESS_MESA, ptr::null_mut(), ptr::null());
+            if display != NO_DISPLAY {
+                return display;
+            }
+        }
+        (self.get_display)(ptr::null_mut())
+    }
+
+    /// Turns the last EGL error into a `HeadlessError` for the failed `call`.
+    pub fn error(&self, call: &'static str) -> HeadlessError {
+        HeadlessError::Egl { call, code: (self.get_error)() }
+    }
+}
+
+impl Drop for Egl {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dlclose(self.library);
+        }
+    }
+}
+
+/// Looks up `name` and reinterprets it as the function pointer type `F`.
+unsafe fn symbol<F: Copy>(library: *mut libc::c_void, name: &str) -> Result<F, HeadlessError> {
+    let c_name = CString::new(name).unwrap();
+    let symbol = libc::dlsym(library, c_name.as_ptr());
+    if symbol.is_null() {
+        Err(HeadlessError::Load(format!("libEGL.so.1 has no symbol {}", name)))
+    } else {
+        Ok(mem::transmute_copy::<*mut libc::c_void, F>(&symbol))
+    }
+}
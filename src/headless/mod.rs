@@ -0,0 +1,277 @@
+use std::error::Error;
+use std::fmt;
+use std::ptr;
+
+use image::RgbImage;
+
+use capture;
+use context::{ContextId, Surface};
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+mod egl;
+use self::egl::Egl;
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    /// libEGL is missing or incomplete.
+    Load(String),
+    /// An EGL call failed with the given `eglGetError` code.
+    Egl { call: &'static str, code: i32 },
+    /// The offscreen framebuffer is not complete; holds the GL status.
+    Framebuffer(GLenum),
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HeadlessError::Load(ref message) => write!(f, "failed to load EGL: {}", message),
+            HeadlessError::Egl { call, code } => write!(f, "{} failed with EGL error {:#x}", call, code),
+            HeadlessError::Framebuffer(status) => write!(f, "offscreen framebuffer is incomplete ({:#x})", status),
+        }
+    }
+}
+
+impl Error for HeadlessError {}
+
+/// An OpenGL 3.3 core context without a window, rendering into an offscreen
+/// RGBA8 + depth framebuffer.
+///
+/// Uses EGL on Mesa's surfaceless platform, so it runs on llvmpipe without a
+/// display server or GPU. The context is current on the creating thread and
+/// the framebuffer is bound, so `Shader` and the GL object wrappers work as
+/// they do with a `Window`.
+pub struct HeadlessContext {
+    egl: Egl,
+    display: egl::EGLDisplay,
+    context: egl::EGLContext,
+    framebuffer: GLuint,
+    renderbuffers: [GLuint; 2],
+    width: u32,
+    height: u32,
+    context_id: ContextId,
+}
+
+impl HeadlessContext {
+    pub fn new(width: u32, height: u32) -> Result<HeadlessContext, HeadlessError> {
+        let egl = Egl::load()?;
+
+        let display = egl.surfaceless_display();
+        if display == egl::NO_DISPLAY {
+            return Err(egl.error("eglGetDisplay"));
+        }
+        if (egl.initialize)(display, ptr::null_mut(), ptr::null_mut()) == 0 {
+            return Err(egl.error("eglInitialize"));
+        }
+        if (egl.bind_api)(egl::OPENGL_API) == 0 {
+            return Err(egl.error("eglBindAPI"));
+        }
+
+        // The default surface type is window, which the surfaceless platform has no configs for
+        let config_attribs = [egl::SURFACE_TYPE, egl::PBUFFER_BIT, egl::RENDERABLE_TYPE, egl::OPENGL_BIT, egl::NONE];
+        let mut config = ptr::null_mut();
+        let mut num_configs = 0;
+        if (egl.choose_config)(display, config_attribs.as_ptr(), &mut config, 1, &mut num_configs) == 0 || num_configs == 0 {
+            return Err(egl.error("eglChooseConfig"));
+        }
+
+        let context_attribs = [
+            egl::CONTEXT_MAJOR_VERSION, 3,
+            egl::CONTEXT_MINOR_VERSION, 3,
+            egl::CONTEXT_OPENGL_PROFILE_MASK, egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+            egl::NONE,
+        ];
+        let context = (egl.create_context)(display, config, egl::NO_CONTEXT, context_attribs.as_ptr());
+        if context == egl::NO_CONTEXT {
+            return Err(egl.error("eglCreateContext"));
+        }
+        if (egl.make_current)(display, egl::NO_SURFACE, egl::NO_SURFACE, context) == 0 {
+            let error = egl.error("eglMakeCurrent");
+            (egl.destroy_context)(display, context);
+            return Err(error);
+        }
+
+        gl::load_with(|name| egl.proc_address(name));
+
+        let mut headless = HeadlessContext {
+            egl,
+            display,
+            context,
+            framebuffer: 0,
+            renderbuffers: [0; 2],
+            width,
+            height,
+            context_id: ContextId::register(),
+        };
+        headless.create_framebuffer()?;
+
+        Ok(headless)
+    }
+
+    fn create_framebuffer(&mut self) -> Result<(), HeadlessError> {
+        unsafe {
+            gl::GenFramebuffers(1, &mut self.framebuffer);
+            gl::GenRenderbuffers(2, self.renderbuffers.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+
+            let attachments = [(gl::RGBA8, gl::COLOR_ATTACHMENT0), (gl::DEPTH24_STENCIL8, gl::DEPTH_STENCIL_ATTACHMENT)];
+            for (&renderbuffer, &(format, attachment)) in self.renderbuffers.iter().zip(attachments.iter()) {
+                gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+                gl::RenderbufferStorage(gl::RENDERBUFFER, format, self.width as GLsizei, self.height as GLsizei);
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, attachment, gl::RENDERBUFFER, renderbuffer);
+            }
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(HeadlessError::Framebuffer(status));
+            }
+        }
+        self.bind();
+
+        Ok(())
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn context_id(&self) -> ContextId {
+        self.context_id
+    }
+
+    /// The offscreen framebuffer object everything is rendered into.
+    pub fn framebuffer(&self) -> GLuint {
+        self.framebuffer
+    }
+
+    /// Makes the context current on this thread, e.g. after rendering with
+    /// another context. Deletions of GL objects dropped while another context
+    /// was current happen now.
+    pub fn make_current(&self) -> Result<(), HeadlessError> {
+        if (self.egl.make_current)(self.display, egl::NO_SURFACE, egl::NO_SURFACE, self.context) == 0 {
+            return Err(self.egl.error("eglMakeCurrent"));
+        }
+        self.context_id.make_current();
+        Ok(())
+    }
+
+    /// Binds the offscreen framebuffer and resets the viewport to cover it,
+    /// e.g. after rendering into another framebuffer.
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::Viewport(0, 0, self.width as GLsizei, self.height as GLsizei);
+        }
+    }
+
+    /// Reads back the color buffer as tightly packed RGBA8, top row first.
+    pub fn read_pixels(&self) -> Vec<u8> {
+        capture::read_pixels(self.framebuffer, self.width, self.height, gl::RGBA, 4)
+    }
+
+    /// The color buffer as an image, ready to be saved or compared.
+    pub fn screenshot(&self) -> RgbImage {
+        capture::capture(self.framebuffer, self.width, self.height)
+    }
+}
+
+impl Surface for HeadlessContext {
+    fn context_id(&self) -> ContextId {
+        self.context_id
+    }
+
+    fn drawable_size(&self) -> (u32, u32) {
+        self.size()
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        // The framebuffer names only mean something in this context
+        if self.make_current().is_err() {
+            self.context_id.unregister();
+            (self.egl.destroy_context)(self.display, self.context);
+            return;
+        }
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteRenderbuffers(2, self.renderbuffers.as_ptr());
+        }
+        self.context_id.unregister();
+
+        (self.egl.make_current)(self.display, egl::NO_SURFACE, egl::NO_SURFACE, egl::NO_CONTEXT);
+        (self.egl.destroy_context)(self.display, self.context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    use object::{Buffer, BufferTarget, VertexArray};
+    use shader::Shader;
+
+    /// A context; fails the test on machines without a usable EGL rather
+    /// than letting it pass without rendering anything.
+    fn context(width: u32, height: u32) -> HeadlessContext {
+        HeadlessContext::new(width, height)
+            .unwrap_or_else(|err| panic!("no headless GL context ({}); these tests need Mesa's EGL", err))
+    }
+
+    #[test]
+    fn clear_color_is_read_back() {
+        let context = context(4, 2);
+
+        unsafe {
+            gl::ClearColor(1.0, 0.0, 1.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+
+        let pixels = context.read_pixels();
+        assert_eq!(pixels.len(), 4 * 2 * 4);
+        assert!(pixels.chunks(4).all(|pixel| pixel == [255, 0, 255, 255]));
+
+        let screenshot = context.screenshot();
+        assert_eq!(screenshot.dimensions(), (4, 2));
+        assert!(screenshot.pixels().all(|pixel| pixel.data == [255, 0, 255]));
+    }
+
+    #[test]
+    fn shader_draws_offscreen() {
+        let context = context(8, 8);
+
+        let dir = env::temp_dir().join("opengl_sdl2_demo_headless");
+        fs::create_dir_all(&dir).unwrap();
+        let vertex = dir.join("fill.glslv");
+        let fragment = dir.join("fill.glslf");
+        fs::write(&vertex, "#version 330 core\nlayout (location = 0) in vec2 position;\nvoid main() { gl_Position = vec4(position, 0.0, 1.0); }\n").unwrap();
+        fs::write(&fragment, "#version 330 core\nout vec4 color;\nvoid main() { color = vec4(0.0, 1.0, 0.0, 1.0); }\n").unwrap();
+
+        let shader = Shader::from_source(&vertex, &fragment).unwrap();
+
+        // One triangle covering the bottom right half of the framebuffer
+        let vertices: [GLfloat; 6] = [-1.0, -1.0, 1.0, -1.0, 1.0, 1.0];
+        let vao = VertexArray::new();
+        let vbo = Buffer::new(BufferTarget::Array);
+        vao.bind();
+        vbo.data(&vertices, gl::STATIC_DRAW);
+
+        unsafe {
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, ptr::null());
+            gl::EnableVertexAttribArray(0);
+
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            shader.gl_use();
+            gl::DrawArrays(gl::TRIANGLES, 0, 3);
+        }
+
+        let pixels = context.read_pixels();
+        let pixel = |x: usize, y: usize| &pixels[(y * 8 + x) * 4..(y * 8 + x) * 4 + 4];
+        assert_eq!(pixel(7, 7), [0, 255, 0, 255]);
+        assert_eq!(pixel(0, 0), [0, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use sdl2::controller::{self, Button};
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::MouseButton;
+
+use super::{Axis, Input, InputError};
+
+/// One input of an action, with the factor its value is multiplied by.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Binding {
+    pub input: Input,
+    pub scale: f32,
+}
+
+/// Named actions and the inputs bound to them.
+///
+/// A bindings file has one `action = input, input...` line per action;
+/// `#` starts a comment and repeated actions add to the earlier bindings.
+/// Inputs are:
+///
+/// - `key:W`, `key:Space`, `key:LShift`, `key:Up`, `key:F1`... for keys by
+///   their position on a US keyboard layout, so bindings survive other layouts
+/// - `mouse:left`, `mouse:middle`, `mouse:right`, `mouse:x1`, `mouse:x2`
+/// - `mouse:x`, `mouse:y` for the mouse movement of the frame, in pixels
+/// - `wheel:x`, `wheel:y` for the wheel steps of the frame
+/// - `pad:leftx`, `pad:lefty`, `pad:rightx`, `pad:righty`, `pad:lefttrigger`,
+///   `pad:righttrigger` for game controller axes
+/// - `pad:a`, `pad:b`, `pad:x`, `pad:y`, `pad:back`, `pad:guide`, `pad:start`,
+///   `pad:leftstick`, `pad:rightstick`, `pad:leftshoulder`, `pad:rightshoulder`,
+///   `pad:dpup`, `pad:dpdown`, `pad:dpleft`, `pad:dpright` for its buttons
+///
+/// Controller names are those of SDL's controller mappings, on an Xbox
+/// layout; bindings apply to every connected controller.
+///
+/// A leading `-` negates the input and a trailing `* factor` scales it, so
+/// `move_right = key:D, -key:A` makes a digital axis from two keys.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Bindings {
+    actions: HashMap<String, Vec<Binding>>,
+}
+
+impl Bindings {
+    pub fn new() -> Bindings {
+        Bindings::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Bindings, InputError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|error| InputError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Bindings::parse(&source, path)
+    }
+
+    /// Parses the contents of the bindings file at `path`.
+    pub fn parse(source: &str, path: &Path) -> Result<Bindings, InputError> {
+        let mut bindings = Bindings::new();
+
+        for (number, line) in source.lines().enumerate() {
+            let error = |message: String| InputError::Parse {
+                path: path.to_path_buf(),
+                line: number as u32 + 1,
+                message,
+            };
+
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (action, inputs) = match line.find('=') {
+                Some(index) => (line[..index].trim(), &line[index + 1..]),
+                None => return Err(error(format!("expected `action = input`, found `{}`", line))),
+            };
+            if action.is_empty() {
+                return Err(error("binding without an action name".to_string()));
+            }
+
+            for input in inputs.split(',') {
+                let binding = parse_binding(input.trim()).map_err(&error)?;
+                bindings.bind(action, binding.input, binding.scale);
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    /// Adds `input`, multiplied by `scale`, to `action`.
+    pub fn bind(&mut self, action: &str, input: Input, scale: f32) {
+        self.actions.entry(action.to_string())
+            .or_default()
+            .push(Binding { input, scale });
+    }
+
+    /// The bindings of `action`, empty for unknown actions.
+    pub fn get(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+}
+
+fn parse_binding(text: &str) -> Result<Binding, String> {
+    let (text, factor) = match text.find('*') {
+        Some(index) => {
+            let factor = text[index + 1..].trim();
+            let factor = factor.parse::<f32>()
+                .map_err(|_| format!("invalid factor `{}`", factor))?;
+            (text[..index].trim(), factor)
+        },
+        None => (text, 1.0),
+    };
+    let (text, sign) = match text.strip_prefix('-') {
+        Some(rest) => (rest.trim(), -1.0),
+        None => (text, 1.0),
+    };
+
+    let input = parse_input(text).ok_or_else(|| format!("unknown input `{}`", text))?;
+    Ok(Binding { input, scale: sign * factor })
+}
+
+/// Parses a `device:name` input as written in bindings files.
+pub fn parse_input(text: &str) -> Option<Input> {
+    let colon = text.find(':')?;
+    let (device, name) = (&text[..colon], text[colon + 1..].to_ascii_lowercase());
+
+    match device {
+        "key" => parse_scancode(&name).map(Input::Key),
+        "mouse" => match name.as_str() {
+            "x" => Some(Input::MouseMotion(Axis::X)),
+            "y" => Some(Input::MouseMotion(Axis::Y)),
+            "left" => Some(Input::MouseButton(MouseButton::Left)),
+            "middle" => Some(Input::MouseButton(MouseButton::Middle)),
+            "right" => Some(Input::MouseButton(MouseButton::Right)),
+            "x1" => Some(Input::MouseButton(MouseButton::X1)),
+            "x2" => Some(Input::MouseButton(MouseButton::X2)),
+            _ => None,
+        },
+        "wheel" => match name.as_str() {
+            "x" => Some(Input::MouseWheel(Axis::X)),
+            "y" => Some(Input::MouseWheel(Axis::Y)),
+            _ => None,
+        },
+        "pad" => parse_controller_input(&name),
+        _ => None,
+    }
+}
+
+// Like scancodes, SDL's names for controller inputs need SDL, so they are
+// listed here
+fn parse_controller_input(name: &str) -> Option<Input> {
+    let axis = match name {
+        "leftx" => Some(controller::Axis::LeftX),
+        "lefty" => Some(controller::Axis::LeftY),
+        "rightx" => Some(controller::Axis::RightX),
+        "righty" => Some(controller::Axis::RightY),
+        "lefttrigger" => Some(controller::Axis::TriggerLeft),
+        "righttrigger" => Some(controller::Axis::TriggerRight),
+        _ => None,
+    };
+    if let Some(axis) = axis {
+        return Some(Input::ControllerAxis(axis));
+    }
+
+    let button = match name {
+        "a" => Button::A,
+        "b" => Button::B,
+        "x" => Button::X,
+        "y" => Button::Y,
+        "back" => Button::Back,
+        "guide" => Button::Guide,
+        "start" => Button::Start,
+        "leftstick" => Button::LeftStick,
+        "rightstick" => Button::RightStick,
+        "leftshoulder" => Button::LeftShoulder,
+        "rightshoulder" => Button::RightShoulder,
+        "dpup" => Button::DPadUp,
+        "dpdown" => Button::DPadDown,
+        "dpleft" => Button::DPadLeft,
+        "dpright" => Button::DPadRight,
+        _ => return None,
+    };
+    Some(Input::ControllerButton(button))
+}
+
+// SDL_GetScancodeFromName needs SDL to be initialized, so the names are
+// resolved here
+fn parse_scancode(name: &str) -> Option<Scancode> {
+    let bytes = name.as_bytes();
+    if bytes.len() == 1 {
+        return match bytes[0] {
+            letter @ b'a'..=b'z' => Scancode::from_i32(Scancode::A as i32 + (letter - b'a') as i32),
+            b'0' => Some(Scancode::Num0),
+            digit @ b'1'..=b'9' => Scancode::from_i32(Scancode::Num1 as i32 + (digit - b'1') as i32),
+            _ => None,
+        };
+    }
+
+    if let Some(number) = name.strip_prefix('f').and_then(|number| number.parse::<i32>().ok()) {
+        return if (1..=12).contains(&number) {
+            Scancode::from_i32(Scancode::F1 as i32 + number - 1)
+        } else {
+            None
+        };
+    }
+
+    let scancode = match name {
+        "return" | "enter" => Scancode::Return,
+        "escape" => Scancode::Escape,
+        "backspace" => Scancode::Backspace,
+        "tab" => Scancode::Tab,
+        "space" => Scancode::Space,
+        "insert" => Scancode::Insert,
+        "delete" => Scancode::Delete,
+        "home" => Scancode::Home,
+        "end" => Scancode::End,
+        "pageup" => Scancode::PageUp,
+        "pagedown" => Scancode::PageDown,
+        "up" => Scancode::Up,
+        "down" => Scancode::Down,
+        "left" => Scancode::Left,
+        "right" => Scancode::Right,
+        "lshift" => Scancode::LShift,
+        "rshift" => Scancode::RShift,
+        "lctrl" => Scancode::LCtrl,
+        "rctrl" => Scancode::RCtrl,
+        "lalt" => Scancode::LAlt,
+        "ralt" => Scancode::RAlt,
+        _ => return None,
+    };
+    Some(scancode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Bindings, InputError> {
+        Bindings::parse(source, Path::new("test.bindings"))
+    }
+
+    #[test]
+    fn inputs_are_parsed_by_name() {
+        assert_eq!(parse_input("key:W"), Some(Input::Key(Scancode::W)));
+        assert_eq!(parse_input("key:7"), Some(Input::Key(Scancode::Num7)));
+        assert_eq!(parse_input("key:F12"), Some(Input::Key(Scancode::F12)));
+        assert_eq!(parse_input("key:LShift"), Some(Input::Key(Scancode::LShift)));
+        assert_eq!(parse_input("mouse:Right"), Some(Input::MouseButton(MouseButton::Right)));
+        assert_eq!(parse_input("mouse:y"), Some(Input::MouseMotion(Axis::Y)));
+        assert_eq!(parse_input("wheel:y"), Some(Input::MouseWheel(Axis::Y)));
+        assert_eq!(parse_input("pad:RightTrigger"), Some(Input::ControllerAxis(controller::Axis::TriggerRight)));
+        assert_eq!(parse_input("pad:dpleft"), Some(Input::ControllerButton(Button::DPadLeft)));
+
+        assert_eq!(parse_input("key:F13"), None);
+        assert_eq!(parse_input("key:WW"), None);
+        assert_eq!(parse_input("W"), None);
+        assert_eq!(parse_input("pad:z"), None);
+    }
+
+    #[test]
+    fn actions_collect_their_bindings() {
+        let bindings = parse("# movement\n\
+                              move_right = key:D, -key:A\n\
+                              look_x = mouse:x * 0.5 # half speed\n\
+                              move_right = key:Right\n").unwrap();
+
+        assert_eq!(bindings.get("move_right"), &[
+            Binding { input: Input::Key(Scancode::D), scale: 1.0 },
+            Binding { input: Input::Key(Scancode::A), scale: -1.0 },
+            Binding { input: Input::Key(Scancode::Right), scale: 1.0 },
+        ]);
+        assert_eq!(bindings.get("look_x"), &[Binding { input: Input::MouseMotion(Axis::X), scale: 0.5 }]);
+        assert!(bindings.get("jump").is_empty());
+    }
+
+    #[test]
+    fn errors_report_the_line() {
+        match parse("zoom = wheel:y\nfire = mouse:thumb\n") {
+            Err(InputError::Parse { line, ref message, .. }) => {
+                assert_eq!(line, 2);
+                assert!(message.contains("mouse:thumb"), "{}", message);
+            },
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        match parse("look_x = mouse:x * fast") {
+            Err(InputError::Parse { line: 1, .. }) => {},
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
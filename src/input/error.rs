@@ -0,0 +1,39 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum InputError {
+    /// A bindings file could not be opened or read.
+    Io { path: PathBuf, error: io::Error },
+    /// A malformed binding; `line` is 1-based.
+    Parse { path: PathBuf, line: u32, message: String },
+    /// SDL's game controller subsystem or a controller failed to open.
+    Controller(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InputError::Io { ref path, ref error } => {
+                write!(f, "{}: {}", path.display(), error)
+            },
+            InputError::Parse { ref path, line, ref message } => {
+                write!(f, "{}:{}: {}", path.display(), line, message)
+            },
+            InputError::Controller(ref message) => {
+                write!(f, "game controller: {}", message)
+            },
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            InputError::Io { ref error, .. } => Some(error),
+            InputError::Parse { .. } | InputError::Controller(_) => None,
+        }
+    }
+}
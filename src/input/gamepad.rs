@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use sdl2::controller::GameController;
+use sdl2::event::Event;
+use sdl2::{GameControllerSubsystem, Sdl};
+
+use super::InputError;
+
+/// Shapes a raw controller axis: values within `dead_zone` of the center
+/// read 0, the rest is rescaled to start from 0 there and raised to
+/// `exponent`, so exponents above 1 give finer control near the center.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AxisResponse {
+    pub dead_zone: f32,
+    pub exponent: f32,
+}
+
+impl AxisResponse {
+    pub fn new(dead_zone: f32, exponent: f32) -> AxisResponse {
+        AxisResponse { dead_zone, exponent }
+    }
+
+    /// The default for sticks, which rarely rest exactly at the center.
+    pub fn stick() -> AxisResponse {
+        AxisResponse::new(0.15, 2.0)
+    }
+
+    /// The default for triggers.
+    pub fn trigger() -> AxisResponse {
+        AxisResponse::new(0.05, 1.0)
+    }
+
+    /// Shapes one axis, -1.0 to 1.0.
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs().min(1.0);
+        if magnitude <= self.dead_zone {
+            return 0.0;
+        }
+
+        let scaled = (magnitude - self.dead_zone) / (1.0 - self.dead_zone);
+        scaled.powf(self.exponent).copysign(value)
+    }
+
+    /// Shapes both axes of a stick by their combined deflection, so the dead
+    /// zone is round and diagonals keep their direction.
+    pub fn apply_stick(&self, x: f32, y: f32) -> (f32, f32) {
+        let magnitude = x.hypot(y);
+        if magnitude <= self.dead_zone {
+            return (0.0, 0.0);
+        }
+
+        let scale = self.apply(magnitude) / magnitude;
+        (x * scale, y * scale)
+    }
+}
+
+/// The game controllers currently plugged in. SDL only reports the axes and
+/// buttons of opened controllers, so pass it every event: it opens
+/// controllers as they are connected, including those present at startup,
+/// and closes them when they are unplugged.
+pub struct Controllers {
+    subsystem: GameControllerSubsystem,
+    open: HashMap<i32, GameController>,
+}
+
+impl Controllers {
+    pub fn new(sdl: &Sdl) -> Result<Controllers, InputError> {
+        let subsystem = sdl.game_controller().map_err(InputError::Controller)?;
+
+        Ok(Controllers {
+            subsystem,
+            open: HashMap::new(),
+        })
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> Result<(), InputError> {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => {
+                // `which` is a device index here, but an instance id everywhere else
+                let controller = self.subsystem.open(which as u32)
+                    .map_err(|err| InputError::Controller(err.to_string()))?;
+                self.open.insert(controller.instance_id(), controller);
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.open.remove(&which);
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.open.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = String> + '_ {
+        self.open.values().map(GameController::name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-6;
+
+    #[test]
+    fn dead_zone_and_curve() {
+        let response = AxisResponse::new(0.2, 2.0);
+
+        assert_eq!(response.apply(0.15), 0.0);
+        assert_eq!(response.apply(-0.2), 0.0);
+        assert!((response.apply(0.6) - 0.25).abs() < EPSILON);
+        assert!((response.apply(-0.6) + 0.25).abs() < EPSILON);
+        assert_eq!(response.apply(1.0), 1.0);
+        assert_eq!(response.apply(-1.5), -1.0);
+    }
+
+    #[test]
+    fn stick_dead_zone_is_round() {
+        let response = AxisResponse::new(0.2, 1.0);
+
+        // Each axis is inside the dead zone, the deflection is not
+        let (x, y) = response.apply_stick(0.15, 0.15);
+        assert!(x > 0.0 && (x - y).abs() < EPSILON);
+        assert_eq!(response.apply_stick(0.1, 0.1), (0.0, 0.0));
+
+        // Full deflection in a square gate's corner still reads 1
+        let (x, y) = response.apply_stick(1.0, 1.0);
+        assert!((x.hypot(y) - 1.0).abs() < EPSILON);
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::{HashMap, HashSet};
+
+use sdl2::controller::{self, Button};
+use sdl2::event::Event;
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
+
+mod bindings;
+mod gamepad;
+mod error;
+pub use self::bindings::{parse_input, Binding, Bindings};
+pub use self::gamepad::{AxisResponse, Controllers};
+pub use self::error::InputError;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// A physical input that actions can be bound to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Input {
+    /// A key by its position, not by the character it types.
+    Key(Scancode),
+    MouseButton(MouseButton),
+    /// Mouse movement during the frame in pixels, `+Y` downwards.
+    MouseMotion(Axis),
+    /// Wheel steps during the frame, `+Y` away from the user.
+    MouseWheel(Axis),
+    /// A stick or trigger of any game controller after its `AxisResponse`,
+    /// -1.0 to 1.0 with stick `+Y` towards the user, 0.0 to 1.0 for triggers.
+    ControllerAxis(controller::Axis),
+    ControllerButton(Button),
+}
+
+impl Input {
+    /// Keys and buttons, as opposed to axes.
+    pub fn is_digital(&self) -> bool {
+        matches!(*self, Input::Key(_) | Input::MouseButton(_) | Input::ControllerButton(_))
+    }
+}
+
+/// The keyboard, mouse and game controllers as of the current frame, kept up
+/// to date by `app::run`: held keys and buttons come from the event pump's
+/// keyboard and mouse state, presses, movement and controllers from the
+/// frame's events.
+#[derive(Clone, Debug)]
+pub struct InputState {
+    keys: HashSet<Scancode>,
+    buttons: HashSet<MouseButton>,
+    pressed: HashSet<Input>,
+    mouse_motion: (f32, f32),
+    wheel: (f32, f32),
+    /// Raw axes and held buttons by controller instance id.
+    controller_axes: HashMap<(i32, controller::Axis), f32>,
+    controller_buttons: HashSet<(i32, Button)>,
+    stick_response: AxisResponse,
+    trigger_response: AxisResponse,
+}
+
+impl Default for InputState {
+    fn default() -> InputState {
+        InputState {
+            keys: HashSet::new(),
+            buttons: HashSet::new(),
+            pressed: HashSet::new(),
+            mouse_motion: (0.0, 0.0),
+            wheel: (0.0, 0.0),
+            controller_axes: HashMap::new(),
+            controller_buttons: HashSet::new(),
+            stick_response: AxisResponse::stick(),
+            trigger_response: AxisResponse::trigger(),
+        }
+    }
+}
+
+impl InputState {
+    pub fn new() -> InputState {
+        InputState::default()
+    }
+
+    pub fn stick_response(&self) -> AxisResponse {
+        self.stick_response
+    }
+
+    /// Dead zone and curve of controller sticks.
+    pub fn set_stick_response(&mut self, response: AxisResponse) {
+        self.stick_response = response;
+    }
+
+    pub fn trigger_response(&self) -> AxisResponse {
+        self.trigger_response
+    }
+
+    pub fn set_trigger_response(&mut self, response: AxisResponse) {
+        self.trigger_response = response;
+    }
+
+    /// Forgets the presses and movement of the previous frame. Call it
+    /// before handling the frame's events.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.mouse_motion = (0.0, 0.0);
+        self.wheel = (0.0, 0.0);
+    }
+
+    /// Records presses, movement and controllers.
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown { scancode: Some(scancode), repeat: false, .. } => {
+                self.pressed.insert(Input::Key(scancode));
+            },
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                self.pressed.insert(Input::MouseButton(mouse_btn));
+            },
+            Event::MouseMotion { xrel, yrel, .. } => {
+                self.mouse_motion.0 += xrel as f32;
+                self.mouse_motion.1 += yrel as f32;
+            },
+            Event::MouseWheel { x, y, direction, .. } => {
+                let sign = if direction == MouseWheelDirection::Flipped { -1.0 } else { 1.0 };
+                self.wheel.0 += sign * x as f32;
+                self.wheel.1 += sign * y as f32;
+            },
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                let value = (value as f32 / i16::MAX as f32).max(-1.0);
+                self.controller_axes.insert((which, axis), value);
+            },
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.controller_buttons.insert((which, button));
+                self.pressed.insert(Input::ControllerButton(button));
+            },
+            Event::ControllerButtonUp { which, button, .. } => {
+                self.controller_buttons.remove(&(which, button));
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                // Nothing stays held on an unplugged controller
+                self.controller_axes.retain(|&(id, _), _| id != which);
+                self.controller_buttons.retain(|&(id, _)| id != which);
+            },
+            _ => {},
+        }
+    }
+
+    /// Replaces the held keys and mouse buttons, e.g. with
+    /// `event_pump.keyboard_state().pressed_scancodes()` and
+    /// `event_pump.mouse_state().pressed_mouse_buttons()`.
+    pub fn set_held<K, B>(&mut self, keys: K, buttons: B)
+        where K: IntoIterator<Item = Scancode>,
+              B: IntoIterator<Item = MouseButton>
+    {
+        self.keys.clear();
+        self.keys.extend(keys);
+        self.buttons.clear();
+        self.buttons.extend(buttons);
+    }
+
+    pub fn is_key_down(&self, scancode: Scancode) -> bool {
+        self.keys.contains(&scancode)
+    }
+
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    /// Whether `input` went down during this frame. Never true for axes.
+    pub fn was_pressed(&self, input: Input) -> bool {
+        self.pressed.contains(&input)
+    }
+
+    /// Whether `button` is held on any controller.
+    pub fn is_controller_button_down(&self, button: Button) -> bool {
+        self.controller_buttons.iter().any(|&(_, held)| held == button)
+    }
+
+    /// `axis` after its `AxisResponse`, summed over all controllers.
+    pub fn controller_axis(&self, axis: controller::Axis) -> f32 {
+        let mut ids: Vec<i32> = self.controller_axes.keys().map(|&(id, _)| id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let sum: f32 = ids.into_iter().map(|id| self.shaped_axis(id, axis)).sum();
+        sum.clamp(-1.0, 1.0)
+    }
+
+    fn shaped_axis(&self, id: i32, axis: controller::Axis) -> f32 {
+        use sdl2::controller::Axis::*;
+
+        let raw = |axis| self.controller_axes.get(&(id, axis)).cloned().unwrap_or(0.0);
+        let stick = |x, y| self.stick_response.apply_stick(raw(x), raw(y));
+
+        match axis {
+            LeftX => stick(LeftX, LeftY).0,
+            LeftY => stick(LeftX, LeftY).1,
+            RightX => stick(RightX, RightY).0,
+            RightY => stick(RightX, RightY).1,
+            TriggerLeft | TriggerRight => self.trigger_response.apply(raw(axis)),
+        }
+    }
+
+    pub fn mouse_motion(&self) -> (f32, f32) {
+        self.mouse_motion
+    }
+
+    pub fn wheel(&self) -> (f32, f32) {
+        self.wheel
+    }
+
+    /// 1.0 for held keys and buttons, 0.0 for released ones, the movement of
+    /// the frame for mouse axes and the deflection for controller axes.
+    pub fn value(&self, input: Input) -> f32 {
+        let held = |down: bool| if down { 1.0 } else { 0.0 };
+        let axis = |values: (f32, f32), axis| match axis {
+            Axis::X => values.0,
+            Axis::Y => values.1,
+        };
+
+        match input {
+            // A key pressed and released within one frame still counts once
+            Input::Key(scancode) => held(self.is_key_down(scancode) || self.was_pressed(input)),
+            Input::MouseButton(button) => held(self.is_button_down(button) || self.was_pressed(input)),
+            Input::MouseMotion(which) => axis(self.mouse_motion, which),
+            Input::MouseWheel(which) => axis(self.wheel, which),
+            Input::ControllerAxis(which) => self.controller_axis(which),
+            Input::ControllerButton(button) => held(self.is_controller_button_down(button) || self.was_pressed(input)),
+        }
+    }
+
+    /// Looks up actions in `bindings` against this state.
+    pub fn actions<'a>(&'a self, bindings: &'a Bindings) -> Actions<'a> {
+        Actions { state: self, bindings }
+    }
+}
+
+/// Named actions evaluated for the current frame.
+#[derive(Copy, Clone, Debug)]
+pub struct Actions<'a> {
+    state: &'a InputState,
+    bindings: &'a Bindings,
+}
+
+impl<'a> Actions<'a> {
+    /// The sum of the scaled values of every input bound to `action`, e.g.
+    /// -1.0 to 1.0 for an axis bound to two keys. 0.0 for unknown actions.
+    pub fn value(&self, action: &str) -> f32 {
+        self.bindings.get(action).iter()
+            .map(|binding| binding.scale * self.state.value(binding.input))
+            .sum()
+    }
+
+    /// Whether any key or button bound to `action` is held.
+    pub fn is_down(&self, action: &str) -> bool {
+        self.bindings.get(action).iter()
+            .any(|binding| binding.input.is_digital() && self.state.value(binding.input) != 0.0)
+    }
+
+    /// Whether any key or button bound to `action` went down this frame.
+    pub fn was_pressed(&self, action: &str) -> bool {
+        self.bindings.get(action).iter()
+            .any(|binding| self.state.was_pressed(binding.input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    fn bindings() -> Bindings {
+        Bindings::parse("move_right = key:D, -key:A\n\
+                         look_x = mouse:x * 0.5\n\
+                         zoom = wheel:y\n\
+                         boost = key:LShift, mouse:right, pad:leftstick\n\
+                         move_forward = -pad:lefty\n\
+                         move_up = pad:righttrigger, -pad:lefttrigger\n",
+                        Path::new("test.bindings")).unwrap()
+    }
+
+    fn wheel_event(y: i32) -> Event {
+        Event::MouseWheel { timestamp: 0, window_id: 1, which: 0, x: 0, y, direction: MouseWheelDirection::Normal }
+    }
+
+    fn axis_event(which: i32, axis: controller::Axis, value: i16) -> Event {
+        Event::ControllerAxisMotion { timestamp: 0, which, axis, value }
+    }
+
+    #[test]
+    fn held_keys_drive_axes() {
+        let bindings = bindings();
+        let mut state = InputState::new();
+
+        state.set_held(vec![Scancode::A], vec![]);
+        assert_eq!(state.actions(&bindings).value("move_right"), -1.0);
+
+        state.set_held(vec![Scancode::A, Scancode::D], vec![MouseButton::Right]);
+        let actions = state.actions(&bindings);
+        assert_eq!(actions.value("move_right"), 0.0);
+        assert!(actions.is_down("boost"));
+        assert!(!actions.is_down("zoom"));
+    }
+
+    #[test]
+    fn movement_is_per_frame() {
+        let bindings = bindings();
+        let mut state = InputState::new();
+
+        state.handle_event(&wheel_event(2));
+        state.handle_event(&wheel_event(1));
+        assert_eq!(state.actions(&bindings).value("zoom"), 3.0);
+
+        state.begin_frame();
+        assert_eq!(state.actions(&bindings).value("zoom"), 0.0);
+    }
+
+    #[test]
+    fn presses_last_one_frame() {
+        let bindings = bindings();
+        let mut state = InputState::new();
+        let press = Event::MouseButtonDown { timestamp: 0, window_id: 1, which: 0, mouse_btn: MouseButton::Right, x: 0, y: 0 };
+
+        state.handle_event(&press);
+        // Released again before the state was polled
+        state.set_held(vec![], vec![]);
+        assert!(state.actions(&bindings).was_pressed("boost"));
+        assert!(state.actions(&bindings).is_down("boost"));
+
+        state.begin_frame();
+        assert!(!state.actions(&bindings).was_pressed("boost"));
+    }
+
+    #[test]
+    fn controllers_feed_the_same_actions() {
+        let bindings = bindings();
+        let mut state = InputState::new();
+        state.set_stick_response(AxisResponse::new(0.1, 1.0));
+        state.set_trigger_response(AxisResponse::new(0.0, 1.0));
+
+        // Stick pushed fully forward, left trigger half pulled
+        state.handle_event(&axis_event(3, controller::Axis::LeftY, i16::MIN));
+        state.handle_event(&axis_event(3, controller::Axis::TriggerLeft, i16::MAX / 2));
+        state.handle_event(&Event::ControllerButtonDown { timestamp: 0, which: 3, button: Button::LeftStick });
+
+        let actions = state.actions(&bindings);
+        assert_eq!(actions.value("move_forward"), 1.0);
+        assert!((actions.value("move_up") + 0.5).abs() < 0.01, "{}", actions.value("move_up"));
+        assert!(actions.is_down("boost") && actions.was_pressed("boost"));
+
+        // Resting sticks stay in the dead zone
+        state.handle_event(&axis_event(3, controller::Axis::LeftY, 2000));
+        assert_eq!(state.actions(&bindings).value("move_forward"), 0.0);
+    }
+
+    #[test]
+    fn unplugging_releases_the_controller() {
+        let bindings = bindings();
+        let mut state = InputState::new();
+
+        state.handle_event(&axis_event(1, controller::Axis::TriggerRight, i16::MAX));
+        state.handle_event(&Event::ControllerButtonDown { timestamp: 0, which: 1, button: Button::LeftStick });
+        state.handle_event(&Event::ControllerDeviceRemoved { timestamp: 0, which: 1 });
+        state.begin_frame();
+
+        let actions = state.actions(&bindings);
+        assert_eq!(actions.value("move_up"), 0.0);
+        assert!(!actions.is_down("boost"));
+    }
+}
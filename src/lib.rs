@@ -0,0 +1,25 @@
+extern crate base64;
+extern crate cgmath;
+extern crate gltf;
+extern crate image;
+extern crate sdl2;
+#[cfg(target_os = "linux")]
+extern crate libc;
+
+pub mod app;
+pub mod camera;
+pub mod capture;
+pub mod context;
+pub mod gl_gen;
+#[cfg(target_os = "linux")]
+pub mod headless;
+pub mod input;
+#[macro_use]
+pub mod mesh;
+pub mod model;
+pub mod object;
+pub mod scene;
+pub mod shader;
+pub mod skybox;
+pub mod texture;
+pub mod window;
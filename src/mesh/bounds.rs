@@ -0,0 +1,76 @@
+use cgmath::{InnerSpace, Vector3};
+
+/// An axis-aligned box, e.g. around the vertices of a model.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl BoundingBox {
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> BoundingBox {
+        BoundingBox { min, max }
+    }
+
+    /// The smallest box containing every point, or `None` for no points.
+    pub fn from_points<I: IntoIterator<Item = Vector3<f32>>>(points: I) -> Option<BoundingBox> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(BoundingBox::new(first, first), |bounds, point| bounds.extend(point)))
+    }
+
+    /// The box grown to contain `point`.
+    pub fn extend(self, point: Vector3<f32>) -> BoundingBox {
+        BoundingBox {
+            min: Vector3::new(self.min.x.min(point.x), self.min.y.min(point.y), self.min.z.min(point.z)),
+            max: Vector3::new(self.max.x.max(point.x), self.max.y.max(point.y), self.max.z.max(point.z)),
+        }
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(self, other: BoundingBox) -> BoundingBox {
+        self.extend(other.min).extend(other.max)
+    }
+
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+
+    /// The radius of the sphere around `center` touching the corners.
+    pub fn radius(&self) -> f32 {
+        self.size().magnitude() * 0.5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_points_spans_every_point() {
+        let bounds = BoundingBox::from_points(vec![
+            Vector3::new(1.0, -2.0, 0.0),
+            Vector3::new(-1.0, 4.0, 2.0),
+            Vector3::new(0.0, 0.0, -2.0),
+        ]).unwrap();
+
+        assert_eq!(bounds.min, Vector3::new(-1.0, -2.0, -2.0));
+        assert_eq!(bounds.max, Vector3::new(1.0, 4.0, 2.0));
+        assert_eq!(bounds.center(), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(bounds.radius(), (4.0f32 + 36.0 + 16.0).sqrt() * 0.5);
+
+        assert_eq!(BoundingBox::from_points(Vec::new()), None);
+    }
+
+    #[test]
+    fn union_contains_both() {
+        let a = BoundingBox::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0));
+        let b = BoundingBox::new(Vector3::new(-1.0, 0.5, 0.5), Vector3::new(0.5, 2.0, 0.5));
+
+        assert_eq!(a.union(b), BoundingBox::new(Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 2.0, 1.0)));
+    }
+}
@@ -0,0 +1,247 @@
+use std::mem;
+
+use cgmath::{Vector2, Vector3, Vector4};
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+/// One vertex input: which `layout (location = N)` it feeds and where it
+/// lives inside the vertex.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub location: GLuint,
+    pub components: GLint,
+    pub ty: GLenum,
+    /// Integer data is mapped to [0, 1] / [-1, 1] floats instead of being
+    /// converted as is.
+    pub normalized: bool,
+    /// Byte offset from the start of the vertex.
+    pub offset: usize,
+}
+
+impl VertexAttribute {
+    /// Integer attributes that are not normalized reach the shader as
+    /// `int`/`uint` and need `glVertexAttribIPointer`.
+    pub fn is_integer(&self) -> bool {
+        !self.normalized && matches!(self.ty,
+            gl::BYTE | gl::UNSIGNED_BYTE | gl::SHORT | gl::UNSIGNED_SHORT | gl::INT | gl::UNSIGNED_INT)
+    }
+}
+
+/// The attributes of one interleaved vertex type and its size in bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VertexLayout {
+    pub stride: usize,
+    pub attributes: Vec<VertexAttribute>,
+}
+
+impl VertexLayout {
+    pub fn new(stride: usize) -> VertexLayout {
+        VertexLayout {
+            stride,
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn attribute(mut self, attribute: VertexAttribute) -> VertexLayout {
+        self.attributes.push(attribute);
+        self
+    }
+
+    /// Locations this layout provides, e.g. for `ProgramReflection::missing_attributes`;
+    /// `ProgramReflection::check_layout` also compares the component counts.
+    pub fn locations(&self) -> Vec<GLuint> {
+        self.attributes.iter().map(|attribute| attribute.location).collect()
+    }
+
+    /// Points every attribute at the buffer bound to `GL_ARRAY_BUFFER` and
+    /// enables it in the bound vertex array.
+    pub fn apply(&self) {
+        for attribute in &self.attributes {
+            let offset = attribute.offset as *const _;
+            unsafe {
+                if attribute.is_integer() {
+                    gl::VertexAttribIPointer(attribute.location, attribute.components, attribute.ty,
+                                             self.stride as GLsizei, offset);
+                } else {
+                    let normalized = if attribute.normalized { gl::TRUE } else { gl::FALSE };
+                    gl::VertexAttribPointer(attribute.location, attribute.components, attribute.ty,
+                                            normalized, self.stride as GLsizei, offset);
+                }
+                gl::EnableVertexAttribArray(attribute.location);
+            }
+        }
+    }
+}
+
+/// A vertex struct whose layout is known. Implement it with `impl_vertex!`.
+pub trait Vertex: Copy {
+    fn layout() -> VertexLayout;
+}
+
+/// A field type that can be a vertex attribute.
+pub trait VertexComponent {
+    const COMPONENTS: GLint;
+    const TYPE: GLenum;
+    const NORMALIZED: bool = false;
+}
+
+/// Marks an integer attribute as normalized, e.g. `Normalized<[u8; 4]>` for
+/// an RGBA color read as a `vec4` in [0, 1].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[repr(transparent)]
+pub struct Normalized<T>(pub T);
+
+impl<T: VertexComponent> VertexComponent for Normalized<T> {
+    const COMPONENTS: GLint = T::COMPONENTS;
+    const TYPE: GLenum = T::TYPE;
+    const NORMALIZED: bool = true;
+}
+
+macro_rules! vertex_component {
+    ($($scalar:ty => $gl_type:expr),*) => {
+        $(
+            impl VertexComponent for $scalar {
+                const COMPONENTS: GLint = 1;
+                const TYPE: GLenum = $gl_type;
+            }
+
+            impl VertexComponent for [$scalar; 2] {
+                const COMPONENTS: GLint = 2;
+                const TYPE: GLenum = $gl_type;
+            }
+
+            impl VertexComponent for [$scalar; 3] {
+                const COMPONENTS: GLint = 3;
+                const TYPE: GLenum = $gl_type;
+            }
+
+            impl VertexComponent for [$scalar; 4] {
+                const COMPONENTS: GLint = 4;
+                const TYPE: GLenum = $gl_type;
+            }
+
+            impl VertexComponent for Vector2<$scalar> {
+                const COMPONENTS: GLint = 2;
+                const TYPE: GLenum = $gl_type;
+            }
+
+            impl VertexComponent for Vector3<$scalar> {
+                const COMPONENTS: GLint = 3;
+                const TYPE: GLenum = $gl_type;
+            }
+
+            impl VertexComponent for Vector4<$scalar> {
+                const COMPONENTS: GLint = 4;
+                const TYPE: GLenum = $gl_type;
+            }
+        )*
+    };
+}
+
+vertex_component! {
+    f32 => gl::FLOAT,
+    i8 => gl::BYTE,
+    u8 => gl::UNSIGNED_BYTE,
+    i16 => gl::SHORT,
+    u16 => gl::UNSIGNED_SHORT,
+    i32 => gl::INT,
+    u32 => gl::UNSIGNED_INT
+}
+
+/// Describes the field `field` points to. Used by `impl_vertex!`, which only
+/// has the field name and lets inference pick `T`.
+#[doc(hidden)]
+pub fn attribute_of<V, T: VertexComponent>(vertex: *const V, field: *const T, location: GLuint) -> VertexAttribute {
+    debug_assert!(mem::size_of::<T>() > 0);
+    VertexAttribute {
+        location,
+        components: T::COMPONENTS,
+        ty: T::TYPE,
+        normalized: T::NORMALIZED,
+        offset: field as usize - vertex as usize,
+    }
+}
+
+/// Implements `Vertex` for a `#[repr(C)]` struct by mapping fields to
+/// shader locations. Component counts, types and offsets come from the field
+/// types.
+///
+/// ```
+/// #[macro_use]
+/// extern crate opengl_sdl2_demo;
+///
+/// use opengl_sdl2_demo::mesh::{Normalized, Vertex};
+///
+/// #[derive(Copy, Clone)]
+/// #[repr(C)]
+/// struct ColoredVertex {
+///     position: [f32; 3],
+///     color: Normalized<[u8; 4]>,
+/// }
+///
+/// impl_vertex!(ColoredVertex { position: 0, color: 1 });
+///
+/// # fn main() {
+/// let layout = ColoredVertex::layout();
+/// assert_eq!(layout.stride, 16);
+/// assert_eq!(layout.attributes[1].offset, 12);
+/// assert!(layout.attributes[1].normalized);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! impl_vertex {
+    ($vertex:ty { $($field:ident: $location:expr),* $(,)* }) => {
+        impl $crate::mesh::Vertex for $vertex {
+            fn layout() -> $crate::mesh::VertexLayout {
+                let vertex = ::std::mem::MaybeUninit::<$vertex>::uninit();
+                let base = vertex.as_ptr();
+                $crate::mesh::VertexLayout::new(::std::mem::size_of::<$vertex>())
+                    $(.attribute($crate::mesh::attribute_of(
+                        base,
+                        // Only the address is taken, the field is never read
+                        unsafe { ::std::ptr::addr_of!((*base).$field) },
+                        $location)))*
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Copy, Clone)]
+    #[repr(C)]
+    struct TexturedVertex {
+        position: Vector3<f32>,
+        color: Normalized<[u8; 4]>,
+        tex_coords: [f32; 2],
+        bone: u32,
+    }
+
+    impl_vertex!(TexturedVertex { position: 0, color: 1, tex_coords: 2, bone: 5 });
+
+    #[test]
+    fn layout_is_computed_from_fields() {
+        let layout = TexturedVertex::layout();
+
+        assert_eq!(layout.stride, 28);
+        assert_eq!(layout.locations(), vec![0, 1, 2, 5]);
+        assert_eq!(layout.attributes[0], VertexAttribute {
+            location: 0, components: 3, ty: gl::FLOAT, normalized: false, offset: 0,
+        });
+        assert_eq!(layout.attributes[1], VertexAttribute {
+            location: 1, components: 4, ty: gl::UNSIGNED_BYTE, normalized: true, offset: 12,
+        });
+        assert_eq!(layout.attributes[2].offset, 16);
+        assert_eq!(layout.attributes[3].offset, 24);
+    }
+
+    #[test]
+    fn only_unnormalized_integers_use_the_integer_path() {
+        let layout = TexturedVertex::layout();
+        let integer: Vec<bool> = layout.attributes.iter().map(VertexAttribute::is_integer).collect();
+        assert_eq!(integer, vec![false, false, false, true]);
+    }
+}
@@ -0,0 +1,102 @@
+use std::ptr;
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+use object::{Buffer, BufferTarget, VertexArray};
+
+mod bounds;
+#[macro_use]
+mod layout;
+pub use self::bounds::BoundingBox;
+pub use self::layout::{Normalized, Vertex, VertexAttribute, VertexComponent, VertexLayout};
+#[doc(hidden)]
+pub use self::layout::attribute_of;
+
+/// Vertices (and optionally indices) uploaded to the GPU together with the
+/// vertex array that describes them.
+#[derive(Debug)]
+pub struct Mesh {
+    vao: VertexArray,
+    _vertices: Buffer,
+    indices: Option<Buffer>,
+    layout: VertexLayout,
+    count: GLsizei,
+    primitive: GLenum,
+}
+
+impl Mesh {
+    /// A non-indexed triangle mesh.
+    pub fn new<V: Vertex>(vertices: &[V]) -> Mesh {
+        Self::build(vertices, None)
+    }
+
+    /// A triangle mesh drawn through `indices`.
+    pub fn with_indices<V: Vertex>(vertices: &[V], indices: &[GLuint]) -> Mesh {
+        Self::build(vertices, Some(indices))
+    }
+
+    fn build<V: Vertex>(vertices: &[V], indices: Option<&[GLuint]>) -> Mesh {
+        let layout = V::layout();
+
+        let vao = VertexArray::new();
+        vao.bind();
+
+        let vertex_buffer = Buffer::new(BufferTarget::Array);
+        vertex_buffer.data(vertices, gl::STATIC_DRAW);
+        layout.apply();
+
+        // The element buffer binding is recorded in the VAO
+        let index_buffer = indices.map(|indices| {
+            let buffer = Buffer::new(BufferTarget::ElementArray);
+            buffer.data(indices, gl::STATIC_DRAW);
+            buffer
+        });
+
+        vao.unbind();
+
+        Mesh {
+            vao,
+            _vertices: vertex_buffer,
+            indices: index_buffer,
+            layout,
+            count: indices.map_or(vertices.len(), |indices| indices.len()) as GLsizei,
+            primitive: gl::TRIANGLES,
+        }
+    }
+
+    /// Draws with another primitive type, e.g. `gl::LINES` or `gl::TRIANGLE_STRIP`.
+    pub fn primitive(mut self, primitive: GLenum) -> Mesh {
+        self.primitive = primitive;
+        self
+    }
+
+    pub fn layout(&self) -> &VertexLayout {
+        &self.layout
+    }
+
+    pub fn vertex_array(&self) -> &VertexArray {
+        &self.vao
+    }
+
+    /// Number of vertices drawn, i.e. the index count for indexed meshes.
+    pub fn count(&self) -> GLsizei {
+        self.count
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.indices.is_some()
+    }
+
+    /// Draws the whole mesh with the program currently in use.
+    pub fn draw(&self) {
+        self.vao.bind();
+        unsafe {
+            if self.is_indexed() {
+                gl::DrawElements(self.primitive, self.count, gl::UNSIGNED_INT, ptr::null());
+            } else {
+                gl::DrawArrays(self.primitive, 0, self.count);
+            }
+        }
+        self.vao.unbind();
+    }
+}
@@ -0,0 +1,40 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use texture::TextureError;
+
+#[derive(Debug)]
+pub enum ModelError {
+    /// A model or material file could not be opened or read.
+    Io { path: PathBuf, error: io::Error },
+    /// A malformed statement; `line` is 1-based.
+    Parse { path: PathBuf, line: u32, message: String },
+    /// A texture referenced by a material could not be loaded.
+    Texture(TextureError),
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ModelError::Io { ref path, ref error } => {
+                write!(f, "{}: {}", path.display(), error)
+            },
+            ModelError::Parse { ref path, line, ref message } => {
+                write!(f, "{}:{}: {}", path.display(), line, message)
+            },
+            ModelError::Texture(ref error) => write!(f, "failed to load texture: {}", error),
+        }
+    }
+}
+
+impl Error for ModelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ModelError::Io { ref error, .. } => Some(error),
+            ModelError::Texture(ref error) => Some(error),
+            ModelError::Parse { .. } => None,
+        }
+    }
+}
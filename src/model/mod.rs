@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use cgmath::Vector3;
+
+use mesh::{BoundingBox, Mesh};
+use texture::Texture2D;
+
+mod error;
+mod mtl;
+mod obj;
+pub use self::error::ModelError;
+pub use self::mtl::{parse_mtl, MaterialInfo};
+pub use self::obj::{parse_obj, MeshData, ObjData};
+
+/// The vertex format of loaded models: position at location 0, normal at 1
+/// and texture coordinates at 2.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(C)]
+pub struct ModelVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub tex_coords: [f32; 2],
+}
+
+impl_vertex!(ModelVertex { position: 0, normal: 1, tex_coords: 2 });
+
+/// A material with its texture maps uploaded.
+#[derive(Debug)]
+pub struct Material {
+    pub name: String,
+    pub ambient: Vector3<f32>,
+    pub diffuse: Vector3<f32>,
+    pub specular: Vector3<f32>,
+    pub shininess: f32,
+    pub opacity: f32,
+    /// Textures are shared between materials that use the same file.
+    pub diffuse_texture: Option<Rc<Texture2D>>,
+    pub specular_texture: Option<Rc<Texture2D>>,
+    pub normal_texture: Option<Rc<Texture2D>>,
+}
+
+/// One part of a model drawn with a single material.
+#[derive(Debug)]
+pub struct ModelMesh {
+    pub name: String,
+    pub mesh: Mesh,
+    /// Index into `Model::materials`.
+    pub material: Option<usize>,
+}
+
+/// A Wavefront OBJ model with its MTL materials, ready to draw.
+#[derive(Debug)]
+pub struct Model {
+    meshes: Vec<ModelMesh>,
+    materials: Vec<Material>,
+    bounds: Option<BoundingBox>,
+}
+
+impl Model {
+    /// Loads an `.obj` file, the material libraries it references and their
+    /// textures. Needs a current OpenGL context.
+    ///
+    /// `usemtl` names that no library defines leave the mesh without a
+    /// material.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Model, ModelError> {
+        let path = path.as_ref();
+        let obj = parse_obj(&read(path)?, path)?;
+
+        let mut infos = Vec::new();
+        for library in &obj.material_libraries {
+            infos.extend(parse_mtl(&read(library)?, library)?);
+        }
+
+        let mut textures = HashMap::new();
+        let mut materials = Vec::with_capacity(infos.len());
+        for info in infos {
+            materials.push(Material {
+                diffuse_texture: load_map(&info.diffuse_map, &mut textures)?,
+                specular_texture: load_map(&info.specular_map, &mut textures)?,
+                normal_texture: load_map(&info.normal_map, &mut textures)?,
+                name: info.name,
+                ambient: info.ambient,
+                diffuse: info.diffuse,
+                specular: info.specular,
+                shininess: info.shininess,
+                opacity: info.opacity,
+            });
+        }
+
+        let bounds = BoundingBox::from_points(obj.meshes.iter()
+            .flat_map(|data| data.vertices.iter().map(|vertex| vertex.position.into())));
+
+        let meshes = obj.meshes.iter()
+            .map(|data| ModelMesh {
+                name: data.name.clone(),
+                mesh: Mesh::with_indices(&data.vertices, &data.indices),
+                material: data.material.as_ref()
+                    .and_then(|name| materials.iter().position(|material| material.name == *name)),
+            })
+            .collect();
+
+        Ok(Model {
+            meshes,
+            materials,
+            bounds,
+        })
+    }
+
+    pub fn meshes(&self) -> &[ModelMesh] {
+        &self.meshes
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    /// The box around every vertex, `None` for a model without vertices.
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        self.bounds
+    }
+
+    pub fn material(&self, mesh: &ModelMesh) -> Option<&Material> {
+        mesh.material.map(|index| &self.materials[index])
+    }
+
+    /// Draws every mesh with its diffuse texture on unit 0 and its specular
+    /// texture on unit 1.
+    pub fn draw(&self) {
+        self.draw_with(|_, material| {
+            if let Some(material) = material {
+                if let Some(ref texture) = material.diffuse_texture {
+                    texture.bind_to_unit(0);
+                }
+                if let Some(ref texture) = material.specular_texture {
+                    texture.bind_to_unit(1);
+                }
+            }
+        });
+    }
+
+    /// Draws every mesh, calling `setup` first so it can bind textures or set
+    /// material uniforms.
+    pub fn draw_with<F: FnMut(&ModelMesh, Option<&Material>)>(&self, mut setup: F) {
+        for mesh in &self.meshes {
+            setup(mesh, self.material(mesh));
+            mesh.mesh.draw();
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<String, ModelError> {
+    fs::read_to_string(path).map_err(|error| ModelError::Io {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+fn load_map(path: &Option<PathBuf>, textures: &mut HashMap<PathBuf, Rc<Texture2D>>) -> Result<Option<Rc<Texture2D>>, ModelError> {
+    let path = match *path {
+        Some(ref path) => path,
+        None => return Ok(None),
+    };
+
+    if let Some(texture) = textures.get(path) {
+        return Ok(Some(texture.clone()));
+    }
+
+    let texture = Rc::new(load_texture(path)?);
+    textures.insert(path.clone(), texture.clone());
+    Ok(Some(texture))
+}
+
+// OBJ texture coordinates start at the bottom left
+fn load_texture(path: &Path) -> Result<Texture2D, ModelError> {
+    Texture2D::builder()
+        .flip_vertically(true)
+        .open(path)
+        .map_err(ModelError::Texture)
+}
@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+
+use cgmath::Vector3;
+
+use super::ModelError;
+
+/// One `newmtl` block of a material library. Texture paths are resolved
+/// relative to the library file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialInfo {
+    pub name: String,
+    /// `Ka`
+    pub ambient: Vector3<f32>,
+    /// `Kd`
+    pub diffuse: Vector3<f32>,
+    /// `Ks`
+    pub specular: Vector3<f32>,
+    /// `Ns`
+    pub shininess: f32,
+    /// `d`, or `1 - Tr`
+    pub opacity: f32,
+    /// `map_Kd`
+    pub diffuse_map: Option<PathBuf>,
+    /// `map_Ks`
+    pub specular_map: Option<PathBuf>,
+    /// `map_Bump`, `bump` or `norm`
+    pub normal_map: Option<PathBuf>,
+}
+
+impl MaterialInfo {
+    pub fn new(name: &str) -> MaterialInfo {
+        MaterialInfo {
+            name: name.to_string(),
+            ambient: Vector3::new(0.0, 0.0, 0.0),
+            diffuse: Vector3::new(1.0, 1.0, 1.0),
+            specular: Vector3::new(0.0, 0.0, 0.0),
+            shininess: 0.0,
+            opacity: 1.0,
+            diffuse_map: None,
+            specular_map: None,
+            normal_map: None,
+        }
+    }
+}
+
+/// Parses the contents of the `.mtl` file at `path`. Unsupported statements
+/// are skipped.
+pub fn parse_mtl(source: &str, path: &Path) -> Result<Vec<MaterialInfo>, ModelError> {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut materials: Vec<MaterialInfo> = Vec::new();
+
+    for (number, line) in source.lines().enumerate() {
+        let error = |message: String| ModelError::Parse {
+            path: path.to_path_buf(),
+            line: number as u32 + 1,
+            message,
+        };
+
+        let line = line.split('#').next().unwrap().trim();
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+
+        if keyword == "newmtl" {
+            if args.is_empty() {
+                return Err(error("`newmtl` without a name".to_string()));
+            }
+            materials.push(MaterialInfo::new(&args.join(" ")));
+            continue;
+        }
+
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => return Err(error(format!("`{}` before the first `newmtl`", keyword))),
+        };
+
+        match keyword {
+            "Ka" => material.ambient = parse_color(&args).map_err(error)?,
+            "Kd" => material.diffuse = parse_color(&args).map_err(error)?,
+            "Ks" => material.specular = parse_color(&args).map_err(error)?,
+            "Ns" => material.shininess = parse_scalar(&args).map_err(error)?,
+            "d" => material.opacity = parse_scalar(&args).map_err(error)?,
+            "Tr" => material.opacity = 1.0 - parse_scalar(&args).map_err(error)?,
+            "map_Kd" => material.diffuse_map = Some(parse_map(&args, dir).map_err(error)?),
+            "map_Ks" => material.specular_map = Some(parse_map(&args, dir).map_err(error)?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => {
+                material.normal_map = Some(parse_map(&args, dir).map_err(error)?)
+            },
+            _ => {},
+        }
+    }
+
+    Ok(materials)
+}
+
+pub(crate) fn parse_f32(word: &str) -> Result<f32, String> {
+    word.parse().map_err(|_| format!("`{}` is not a number", word))
+}
+
+fn parse_scalar(args: &[&str]) -> Result<f32, String> {
+    match args.first() {
+        Some(word) => parse_f32(word),
+        None => Err("missing value".to_string()),
+    }
+}
+
+// `Kd r [g b]`; a single value is a gray level
+fn parse_color(args: &[&str]) -> Result<Vector3<f32>, String> {
+    match args.len() {
+        1 => {
+            let gray = parse_f32(args[0])?;
+            Ok(Vector3::new(gray, gray, gray))
+        },
+        3 => Ok(Vector3::new(parse_f32(args[0])?, parse_f32(args[1])?, parse_f32(args[2])?)),
+        _ => Err(format!("expected 1 or 3 color components, found {}", args.len())),
+    }
+}
+
+// `map_Kd [-option value...] file`; options such as `-s` or `-bm` are
+// ignored and the last word is taken as the file name
+fn parse_map(args: &[&str], dir: &Path) -> Result<PathBuf, String> {
+    match args.last() {
+        Some(file) => Ok(dir.join(file.replace('\\', "/"))),
+        None => Err("missing texture file name".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colors_and_maps() {
+        let source = "
+            # two materials
+            newmtl crate
+            Ka 0.1
+            Kd 0.8 0.7 0.6
+            Ns 32
+            map_Kd -s 1 1 1 textures\\crate.png
+            map_Bump crate_normal.png
+
+            newmtl glass
+            Tr 0.75
+        ";
+        let materials = parse_mtl(source, Path::new("models/scene.mtl")).unwrap();
+
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].ambient, Vector3::new(0.1, 0.1, 0.1));
+        assert_eq!(materials[0].diffuse, Vector3::new(0.8, 0.7, 0.6));
+        assert_eq!(materials[0].shininess, 32.0);
+        assert_eq!(materials[0].diffuse_map, Some(PathBuf::from("models/textures/crate.png")));
+        assert_eq!(materials[0].normal_map, Some(PathBuf::from("models/crate_normal.png")));
+        assert_eq!(materials[1].name, "glass");
+        assert_eq!(materials[1].opacity, 0.25);
+    }
+
+    #[test]
+    fn statements_need_a_material() {
+        match parse_mtl("\nKd 1 1 1", Path::new("a.mtl")) {
+            Err(ModelError::Parse { line, .. }) => assert_eq!(line, 2),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use cgmath::{InnerSpace, Vector3};
+
+use gl_gen::gl::types::*;
+
+use super::mtl::parse_f32;
+use super::{ModelError, ModelVertex};
+
+/// The contents of an `.obj` file, split into one `MeshData` per object,
+/// group and material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjData {
+    /// `mtllib` files, resolved relative to the `.obj` file.
+    pub material_libraries: Vec<PathBuf>,
+    pub meshes: Vec<MeshData>,
+}
+
+/// Triangulated, indexed vertices that share one material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshData {
+    pub name: String,
+    /// The `usemtl` name in effect.
+    pub material: Option<String>,
+    pub vertices: Vec<ModelVertex>,
+    pub indices: Vec<GLuint>,
+}
+
+// Indices into the position, texture coordinate and normal lists
+type Corner = (usize, Option<usize>, Option<usize>);
+
+struct MeshBuilder {
+    name: String,
+    material: Option<String>,
+    vertices: Vec<ModelVertex>,
+    indices: Vec<GLuint>,
+    corners: HashMap<Corner, GLuint>,
+    // Position index of every vertex, to smooth generated normals across
+    // texture seams
+    positions: Vec<usize>,
+    missing_normals: bool,
+}
+
+impl MeshBuilder {
+    fn new(name: String, material: Option<String>) -> MeshBuilder {
+        MeshBuilder {
+            name,
+            material,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            corners: HashMap::new(),
+            positions: Vec::new(),
+            missing_normals: false,
+        }
+    }
+
+    fn vertex(&mut self, corner: Corner, data: &ObjLists) -> GLuint {
+        if let Some(&index) = self.corners.get(&corner) {
+            return index;
+        }
+
+        let (position, tex_coords, normal) = corner;
+        self.missing_normals |= normal.is_none();
+
+        let index = self.vertices.len() as GLuint;
+        self.vertices.push(ModelVertex {
+            position: data.positions[position],
+            normal: normal.map_or([0.0; 3], |normal| data.normals[normal]),
+            tex_coords: tex_coords.map_or([0.0; 2], |tex_coords| data.tex_coords[tex_coords]),
+        });
+        self.positions.push(position);
+        self.corners.insert(corner, index);
+        index
+    }
+
+    /// Gives vertices without a `vn` the area weighted average of the face
+    /// normals around their position.
+    fn generate_normals(&mut self, corners_without_normal: &[bool]) {
+        let mut sums: HashMap<usize, Vector3<f32>> = HashMap::new();
+        for triangle in self.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(self.vertices[triangle[i] as usize].position));
+            let face = (b - a).cross(c - a);
+            for &index in triangle {
+                *sums.entry(self.positions[index as usize]).or_insert(Vector3::new(0.0, 0.0, 0.0)) += face;
+            }
+        }
+
+        for (index, vertex) in self.vertices.iter_mut().enumerate() {
+            if corners_without_normal[index] {
+                let sum = sums[&self.positions[index]];
+                if sum.magnitude2() > 0.0 {
+                    vertex.normal = sum.normalize().into();
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> MeshData {
+        if self.missing_normals {
+            let mut without_normal = vec![false; self.vertices.len()];
+            for (&(_, _, normal), &index) in &self.corners {
+                without_normal[index as usize] = normal.is_none();
+            }
+            self.generate_normals(&without_normal);
+        }
+
+        MeshData {
+            name: self.name,
+            material: self.material,
+            vertices: self.vertices,
+            indices: self.indices,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ObjLists {
+    positions: Vec<[f32; 3]>,
+    tex_coords: Vec<[f32; 2]>,
+    normals: Vec<[f32; 3]>,
+}
+
+/// Parses the contents of the `.obj` file at `path`.
+///
+/// Polygons are triangulated as fans, which is correct for the convex faces
+/// exporters write. `v/vt/vn` corners that repeat are shared through the
+/// index buffer. Missing texture coordinates are zero; missing normals are
+/// generated from the faces. Unsupported statements (`s`, `l`, curves...) are
+/// skipped.
+pub fn parse_obj(source: &str, path: &Path) -> Result<ObjData, ModelError> {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let default_name = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+
+    let mut lists = ObjLists::default();
+    let mut material_libraries = Vec::new();
+    let mut meshes = Vec::new();
+    let mut current = MeshBuilder::new(default_name, None);
+
+    for (number, line) in source.lines().enumerate() {
+        let error = |message: String| ModelError::Parse {
+            path: path.to_path_buf(),
+            line: number as u32 + 1,
+            message,
+        };
+
+        let line = line.split('#').next().unwrap().trim();
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = words.collect();
+
+        match keyword {
+            "v" => lists.positions.push(parse_vector(&args, 3, 4).map_err(error)?),
+            "vn" => lists.normals.push(parse_vector(&args, 3, 3).map_err(error)?),
+            "vt" => {
+                let uv: [f32; 3] = parse_vector(&args, 1, 3).map_err(error)?;
+                lists.tex_coords.push([uv[0], uv[1]]);
+            },
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error(format!("a face needs at least 3 vertices, found {}", args.len())));
+                }
+                let corners = args.iter()
+                    .map(|corner| parse_corner(corner, &lists))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                let indices: Vec<GLuint> = corners.into_iter()
+                    .map(|corner| current.vertex(corner, &lists))
+                    .collect();
+                for i in 1..indices.len() - 1 {
+                    current.indices.extend_from_slice(&[indices[0], indices[i], indices[i + 1]]);
+                }
+            },
+            "o" | "g" => {
+                let next = MeshBuilder::new(args.join(" "), current.material.clone());
+                push_mesh(&mut meshes, mem::replace(&mut current, next));
+            },
+            "usemtl" => {
+                let next = MeshBuilder::new(current.name.clone(), Some(args.join(" ")));
+                push_mesh(&mut meshes, mem::replace(&mut current, next));
+            },
+            "mtllib" => {
+                material_libraries.extend(args.iter().map(|file| dir.join(file.replace('\\', "/"))));
+            },
+            _ => {},
+        }
+    }
+    push_mesh(&mut meshes, current);
+
+    Ok(ObjData {
+        material_libraries,
+        meshes,
+    })
+}
+
+fn push_mesh(meshes: &mut Vec<MeshData>, mesh: MeshBuilder) {
+    if !mesh.indices.is_empty() {
+        meshes.push(mesh.finish());
+    }
+}
+
+// Reads between `min` and `N` numbers; the rest stay zero
+fn parse_vector<const N: usize>(args: &[&str], min: usize, max: usize) -> Result<[f32; N], String> {
+    if args.len() < min || args.len() > max {
+        return Err(format!("expected {} to {} numbers, found {}", min, max, args.len()));
+    }
+
+    let mut vector = [0.0; N];
+    for (component, word) in vector.iter_mut().zip(args) {
+        *component = parse_f32(word)?;
+    }
+    Ok(vector)
+}
+
+// `v`, `v/vt`, `v//vn` or `v/vt/vn`
+fn parse_corner(corner: &str, lists: &ObjLists) -> Result<Corner, String> {
+    let mut parts = corner.split('/');
+    let position = parse_index(parts.next().unwrap_or(""), lists.positions.len(), "vertex")?;
+    let tex_coords = match parts.next() {
+        Some("") | None => None,
+        Some(part) => Some(parse_index(part, lists.tex_coords.len(), "texture coordinate")?),
+    };
+    let normal = match parts.next() {
+        Some("") | None => None,
+        Some(part) => Some(parse_index(part, lists.normals.len(), "normal")?),
+    };
+    Ok((position, tex_coords, normal))
+}
+
+// OBJ indices are 1-based; negative ones count back from the last element
+fn parse_index(word: &str, len: usize, what: &str) -> Result<usize, String> {
+    let index: i64 = word.parse().map_err(|_| format!("`{}` is not a {} index", word, what))?;
+    let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("{} index {} is out of range ({} defined)", what, index, len));
+    }
+    Ok(resolved as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAD: &str = "
+        mtllib quad.mtl
+        v 0 0 0
+        v 1 0 0
+        v 1 1 0
+        v 0 1 0
+        vt 0 0
+        vt 1 0
+        vt 1 1
+        vt 0 1
+        vn 0 0 1
+        o Quad
+        usemtl Red
+        f 1/1/1 2/2/1 3/3/1 4/4/1
+    ";
+
+    #[test]
+    fn polygons_are_triangulated_and_indexed() {
+        let obj = parse_obj(QUAD, Path::new("assets/quad.obj")).unwrap();
+
+        assert_eq!(obj.material_libraries, vec![PathBuf::from("assets/quad.mtl")]);
+        assert_eq!(obj.meshes.len(), 1);
+
+        let mesh = &obj.meshes[0];
+        assert_eq!(mesh.name, "Quad");
+        assert_eq!(mesh.material, Some("Red".to_string()));
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+        assert_eq!(mesh.vertices[2], ModelVertex {
+            position: [1.0, 1.0, 0.0],
+            normal: [0.0, 0.0, 1.0],
+            tex_coords: [1.0, 1.0],
+        });
+    }
+
+    #[test]
+    fn shared_corners_are_deduplicated() {
+        let source = "
+            v 0 0 0
+            v 1 0 0
+            v 1 1 0
+            v 0 1 0
+            f -4 -3 -2
+            f 1 3 4
+        ";
+        let mesh = &parse_obj(source, Path::new("tri.obj")).unwrap().meshes[0];
+
+        assert_eq!(mesh.name, "tri");
+        assert_eq!(mesh.vertices.len(), 4);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+        // Generated from the counter-clockwise winding
+        for vertex in &mesh.vertices {
+            assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn materials_split_meshes() {
+        let source = "
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            usemtl A
+            f 1 2 3
+            usemtl B
+            f 3 2 1
+        ";
+        let meshes = parse_obj(source, Path::new("split.obj")).unwrap().meshes;
+
+        let materials: Vec<_> = meshes.iter().map(|mesh| mesh.material.clone().unwrap()).collect();
+        assert_eq!(materials, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn out_of_range_indices_are_errors() {
+        match parse_obj("v 0 0 0\nf 1 2 3", Path::new("bad.obj")) {
+            Err(ModelError::Parse { line, ref message, .. }) => {
+                assert_eq!(line, 2);
+                assert!(message.contains("out of range"), "{}", message);
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
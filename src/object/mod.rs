@@ -0,0 +1,203 @@
+use std::mem;
+
+use context::{ContextId, GlObject};
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BufferTarget {
+    /// Vertex data (VBO).
+    Array,
+    /// Indices (EBO).
+    ElementArray,
+    Uniform,
+    ShaderStorage,
+}
+
+impl BufferTarget {
+    pub fn gl_enum(self) -> GLenum {
+        match self {
+            BufferTarget::Array => gl::ARRAY_BUFFER,
+            BufferTarget::ElementArray => gl::ELEMENT_ARRAY_BUFFER,
+            BufferTarget::Uniform => gl::UNIFORM_BUFFER,
+            BufferTarget::ShaderStorage => gl::SHADER_STORAGE_BUFFER,
+        }
+    }
+}
+
+/// An owned buffer object, deleted on drop.
+#[derive(Debug)]
+pub struct Buffer {
+    id: GLuint,
+    target: BufferTarget,
+    context: ContextId,
+}
+
+impl Buffer {
+    pub fn new(target: BufferTarget) -> Buffer {
+        let context = ContextId::current();
+        let mut id = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+        }
+
+        Buffer {
+            id,
+            target,
+            context,
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn target(&self) -> BufferTarget {
+        self.target
+    }
+
+    pub fn bind(&self) {
+        self.context.assert_alive("Buffer");
+        unsafe {
+            gl::BindBuffer(self.target.gl_enum(), self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        self.context.assert_alive("Buffer");
+        unsafe {
+            gl::BindBuffer(self.target.gl_enum(), 0);
+        }
+    }
+
+    /// Binds the buffer and uploads `data` with the given usage hint
+    /// (e.g. `gl::STATIC_DRAW`).
+    pub fn data<T>(&self, data: &[T], usage: GLenum) {
+        self.bind();
+        unsafe {
+            gl::BufferData(self.target.gl_enum(),
+                           mem::size_of_val(data) as GLsizeiptr,
+                           data.as_ptr() as *const _,
+                           usage);
+        }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        self.context.delete(GlObject::Buffer(self.id));
+    }
+}
+
+/// An owned vertex array object, deleted on drop.
+#[derive(Debug)]
+pub struct VertexArray {
+    id: GLuint,
+    context: ContextId,
+}
+
+impl VertexArray {
+    pub fn new() -> VertexArray {
+        let context = ContextId::current();
+        let mut id = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut id);
+        }
+
+        VertexArray {
+            id,
+            context,
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn bind(&self) {
+        self.context.assert_alive("VertexArray");
+        unsafe {
+            gl::BindVertexArray(self.id);
+        }
+    }
+
+    pub fn unbind(&self) {
+        self.context.assert_alive("VertexArray");
+        unsafe {
+            gl::BindVertexArray(0);
+        }
+    }
+}
+
+impl Default for VertexArray {
+    fn default() -> VertexArray {
+        VertexArray::new()
+    }
+}
+
+impl Drop for VertexArray {
+    fn drop(&mut self) {
+        self.context.delete(GlObject::VertexArray(self.id));
+    }
+}
+
+/// An owned texture name, deleted on drop.
+#[derive(Debug)]
+pub struct Texture {
+    id: GLuint,
+    target: GLenum,
+    context: ContextId,
+}
+
+impl Texture {
+    /// Creates a texture for `target` (e.g. `gl::TEXTURE_2D`).
+    pub fn new(target: GLenum) -> Texture {
+        let context = ContextId::current();
+        let mut id = 0;
+        unsafe {
+            gl::GenTextures(1, &mut id);
+        }
+
+        Texture {
+            id,
+            target,
+            context,
+        }
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.id
+    }
+
+    pub fn target(&self) -> GLenum {
+        self.target
+    }
+
+    pub fn bind(&self) {
+        self.context.assert_alive("Texture");
+        unsafe {
+            gl::BindTexture(self.target, self.id);
+        }
+    }
+
+    /// Makes `unit` the active texture unit and binds the texture to it.
+    pub fn bind_to_unit(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+        }
+        self.bind();
+    }
+
+    pub fn unbind(&self) {
+        self.context.assert_alive("Texture");
+        unsafe {
+            gl::BindTexture(self.target, 0);
+        }
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        self.context.delete(GlObject::Texture(self.id));
+    }
+}
@@ -0,0 +1,75 @@
+use cgmath::{ortho, Matrix4, Rad};
+
+use gltf;
+
+/// A camera definition; the node that references it places it in the scene.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneCamera {
+    pub name: Option<String>,
+    pub projection: CameraProjection,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CameraProjection {
+    Perspective {
+        yfov: Rad<f32>,
+        /// The aspect ratio the scene was authored for.
+        aspect_ratio: Option<f32>,
+        znear: f32,
+        /// `None` for an infinite far plane.
+        zfar: Option<f32>,
+    },
+    Orthographic {
+        xmag: f32,
+        ymag: f32,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+impl CameraProjection {
+    /// The projection matrix for a viewport with `aspect_ratio`, which
+    /// replaces the authored one so the image is not stretched.
+    pub fn matrix(&self, aspect_ratio: f32) -> Matrix4<f32> {
+        match *self {
+            CameraProjection::Perspective { yfov, znear, zfar: Some(zfar), .. } => {
+                ::cgmath::perspective(yfov, aspect_ratio, znear, zfar)
+            },
+            CameraProjection::Perspective { yfov, znear, zfar: None, .. } => {
+                // The limit of the perspective matrix as zfar goes to infinity
+                let f = 1.0 / (yfov.0 / 2.0).tan();
+                Matrix4::new(f / aspect_ratio, 0.0, 0.0, 0.0,
+                             0.0, f, 0.0, 0.0,
+                             0.0, 0.0, -1.0, -1.0,
+                             0.0, 0.0, -2.0 * znear, 0.0)
+            },
+            CameraProjection::Orthographic { xmag, ymag, znear, zfar } => {
+                ortho(-xmag, xmag, -ymag, ymag, znear, zfar)
+            },
+        }
+    }
+}
+
+impl<'a> From<gltf::Camera<'a>> for SceneCamera {
+    fn from(camera: gltf::Camera<'a>) -> SceneCamera {
+        let projection = match camera.projection() {
+            gltf::camera::Projection::Perspective(perspective) => CameraProjection::Perspective {
+                yfov: Rad(perspective.yfov()),
+                aspect_ratio: perspective.aspect_ratio(),
+                znear: perspective.znear(),
+                zfar: perspective.zfar(),
+            },
+            gltf::camera::Projection::Orthographic(orthographic) => CameraProjection::Orthographic {
+                xmag: orthographic.xmag(),
+                ymag: orthographic.ymag(),
+                znear: orthographic.znear(),
+                zfar: orthographic.zfar(),
+            },
+        };
+
+        SceneCamera {
+            name: camera.name().map(str::to_string),
+            projection,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use base64::DecodeError;
+use gltf;
+use image::ImageError;
+
+use texture::TextureError;
+
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene or a file it references could not be read.
+    Io { path: PathBuf, error: io::Error },
+    /// The document is not valid glTF 2.0.
+    Gltf(gltf::Error),
+    /// A `data:` URI that is not base64 encoded.
+    UnsupportedUri(String),
+    Base64(DecodeError),
+    /// A buffer is missing or shorter than the document says.
+    Buffer { index: usize, message: String },
+    /// A buffer view reaching past the end of its buffer.
+    BufferView { index: usize },
+    Image { index: usize, error: ImageError },
+    Texture { index: usize, error: TextureError },
+    /// A primitive without the required `POSITION` attribute.
+    MissingPositions { mesh: usize },
+    /// A primitive index with no vertex behind it.
+    InvalidIndex { mesh: usize, index: u32, vertices: usize },
+    /// The nodes do not form a set of trees, e.g. a node is its own ancestor.
+    Hierarchy { node: usize, message: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SceneError::Io { ref path, ref error } => write!(f, "{}: {}", path.display(), error),
+            SceneError::Gltf(ref error) => write!(f, "invalid glTF: {}", error),
+            SceneError::UnsupportedUri(ref uri) => write!(f, "unsupported data URI `{}`", uri),
+            SceneError::Base64(ref error) => write!(f, "invalid base64 data: {}", error),
+            SceneError::Buffer { index, ref message } => write!(f, "buffer {}: {}", index, message),
+            SceneError::BufferView { index } => write!(f, "buffer view {} lies outside its buffer", index),
+            SceneError::Image { index, ref error } => write!(f, "image {}: {}", index, error),
+            SceneError::Texture { index, ref error } => write!(f, "texture {}: {}", index, error),
+            SceneError::MissingPositions { mesh } => write!(f, "mesh {} has a primitive without positions", mesh),
+            SceneError::InvalidIndex { mesh, index, vertices } => {
+                write!(f, "mesh {} refers to vertex {} of {}", mesh, index, vertices)
+            },
+            SceneError::Hierarchy { node, ref message } => write!(f, "node {}: {}", node, message),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            SceneError::Io { ref error, .. } => Some(error),
+            SceneError::Gltf(ref error) => Some(error),
+            SceneError::Base64(ref error) => Some(error),
+            SceneError::Image { ref error, .. } => Some(error),
+            SceneError::Texture { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<gltf::Error> for SceneError {
+    fn from(error: gltf::Error) -> SceneError {
+        SceneError::Gltf(error)
+    }
+}
+
+impl From<DecodeError> for SceneError {
+    fn from(error: DecodeError) -> SceneError {
+        SceneError::Base64(error)
+    }
+}
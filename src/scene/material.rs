@@ -0,0 +1,104 @@
+use cgmath::{Vector3, Vector4};
+
+use gltf;
+
+/// A texture slot of a material: which of `Scene::textures` it samples and
+/// with which texture coordinate set.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureRef {
+    pub texture: usize,
+    pub tex_coord: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    Opaque,
+    /// Fragments with alpha below `PbrMaterial::alpha_cutoff` are discarded.
+    Mask,
+    Blend,
+}
+
+/// The metallic-roughness parameters of a glTF material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PbrMaterial {
+    pub name: Option<String>,
+    pub base_color_factor: Vector4<f32>,
+    pub base_color_texture: Option<TextureRef>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    /// Roughness in the green channel, metalness in the blue channel.
+    pub metallic_roughness_texture: Option<TextureRef>,
+    pub normal_texture: Option<TextureRef>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<TextureRef>,
+    pub occlusion_strength: f32,
+    pub emissive_factor: Vector3<f32>,
+    pub emissive_texture: Option<TextureRef>,
+    pub alpha_mode: AlphaMode,
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+}
+
+impl Default for PbrMaterial {
+    /// The material the specification uses for primitives without one.
+    fn default() -> PbrMaterial {
+        PbrMaterial {
+            name: None,
+            base_color_factor: Vector4::new(1.0, 1.0, 1.0, 1.0),
+            base_color_texture: None,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            occlusion_texture: None,
+            occlusion_strength: 1.0,
+            emissive_factor: Vector3::new(0.0, 0.0, 0.0),
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+impl<'a> From<gltf::Material<'a>> for PbrMaterial {
+    fn from(material: gltf::Material<'a>) -> PbrMaterial {
+        let pbr = material.pbr_metallic_roughness();
+        let info = |info: gltf::texture::Info| TextureRef {
+            texture: info.texture().index(),
+            tex_coord: info.tex_coord(),
+        };
+
+        let normal = material.normal_texture();
+        let occlusion = material.occlusion_texture();
+
+        PbrMaterial {
+            name: material.name().map(str::to_string),
+            base_color_factor: pbr.base_color_factor().into(),
+            base_color_texture: pbr.base_color_texture().map(info),
+            metallic_factor: pbr.metallic_factor(),
+            roughness_factor: pbr.roughness_factor(),
+            metallic_roughness_texture: pbr.metallic_roughness_texture().map(info),
+            normal_texture: normal.as_ref().map(|normal| TextureRef {
+                texture: normal.texture().index(),
+                tex_coord: normal.tex_coord(),
+            }),
+            normal_scale: normal.as_ref().map_or(1.0, |normal| normal.scale()),
+            occlusion_texture: occlusion.as_ref().map(|occlusion| TextureRef {
+                texture: occlusion.texture().index(),
+                tex_coord: occlusion.tex_coord(),
+            }),
+            occlusion_strength: occlusion.as_ref().map_or(1.0, |occlusion| occlusion.strength()),
+            emissive_factor: material.emissive_factor().into(),
+            emissive_texture: material.emissive_texture().map(info),
+            alpha_mode: match material.alpha_mode() {
+                gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+                gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+            },
+            alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+            double_sided: material.double_sided(),
+        }
+    }
+}
@@ -0,0 +1,512 @@
+use std::fs;
+use std::path::Path;
+
+use cgmath::{InnerSpace, Matrix4, SquareMatrix, Vector3};
+use gltf;
+use image;
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+use mesh::Mesh;
+use model::ModelVertex;
+use texture::{Filter, MinFilter, Texture2D, TextureBuilder, Wrap};
+
+mod camera;
+mod error;
+mod material;
+mod uri;
+pub use self::camera::{CameraProjection, SceneCamera};
+pub use self::error::SceneError;
+pub use self::material::{AlphaMode, PbrMaterial, TextureRef};
+pub use self::uri::read_uri;
+
+/// One entry of the node hierarchy. Indices refer to the lists of the
+/// `Scene` it belongs to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub name: Option<String>,
+    /// The transform relative to the parent node.
+    pub transform: Matrix4<f32>,
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+    pub camera: Option<usize>,
+}
+
+/// Part of a mesh drawn with one material.
+#[derive(Debug)]
+pub struct Primitive {
+    pub mesh: Mesh,
+    /// Index into `Scene::materials`; `None` means the default material.
+    pub material: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SceneMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<Primitive>,
+}
+
+/// A glTF 2.0 scene (`.gltf` or `.glb`) uploaded to the GPU.
+#[derive(Debug)]
+pub struct Scene {
+    nodes: Vec<Node>,
+    roots: Vec<usize>,
+    meshes: Vec<SceneMesh>,
+    materials: Vec<PbrMaterial>,
+    textures: Vec<Texture2D>,
+    cameras: Vec<SceneCamera>,
+}
+
+impl Scene {
+    /// Loads the default scene of a `.gltf` or `.glb` file, including the
+    /// buffers and images it references. Needs a current OpenGL context.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|error| SceneError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Scene::from_slice(&bytes, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Loads a glTF document held in memory; relative URIs are resolved
+    /// against `base`.
+    pub fn from_slice(bytes: &[u8], base: &Path) -> Result<Scene, SceneError> {
+        let gltf::Gltf { document, mut blob } = gltf::Gltf::from_slice(bytes)?;
+
+        let mut buffers = Vec::new();
+        for buffer in document.buffers() {
+            let data = match buffer.source() {
+                gltf::buffer::Source::Bin => blob.take().ok_or_else(|| SceneError::Buffer {
+                    index: buffer.index(),
+                    message: "refers to the missing binary chunk".to_string(),
+                })?,
+                gltf::buffer::Source::Uri(uri) => read_uri(uri, base)?,
+            };
+            if data.len() < buffer.length() {
+                return Err(SceneError::Buffer {
+                    index: buffer.index(),
+                    message: format!("has {} bytes, {} expected", data.len(), buffer.length()),
+                });
+            }
+            buffers.push(data);
+        }
+
+        let mut images = Vec::new();
+        for image in document.images() {
+            let bytes = match image.source() {
+                gltf::image::Source::View { view, .. } => {
+                    let start = view.offset();
+                    start.checked_add(view.length())
+                        .and_then(|end| buffers.get(view.buffer().index())?.get(start..end))
+                        .ok_or(SceneError::BufferView { index: view.index() })?
+                        .to_vec()
+                },
+                gltf::image::Source::Uri { uri, .. } => read_uri(uri, base)?,
+            };
+            let decoded = image::load_from_memory(&bytes).map_err(|error| SceneError::Image {
+                index: image.index(),
+                error,
+            })?;
+            images.push(decoded);
+        }
+
+        let mut textures = Vec::new();
+        for texture in document.textures() {
+            let uploaded = sampler_options(&texture.sampler())
+                .build(&images[texture.source().index()])
+                .map_err(|error| SceneError::Texture { index: texture.index(), error })?;
+            textures.push(uploaded);
+        }
+
+        let mut meshes = Vec::new();
+        for mesh in document.meshes() {
+            let mut primitives = Vec::new();
+            for primitive in mesh.primitives() {
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+                let positions = match reader.read_positions() {
+                    Some(positions) => positions,
+                    None => return Err(SceneError::MissingPositions { mesh: mesh.index() }),
+                };
+                let mut vertices: Vec<ModelVertex> = positions
+                    .map(|position| ModelVertex {
+                        position,
+                        normal: [0.0; 3],
+                        tex_coords: [0.0; 2],
+                    })
+                    .collect();
+                if let Some(tex_coords) = reader.read_tex_coords(0) {
+                    for (vertex, tex_coords) in vertices.iter_mut().zip(tex_coords.into_f32()) {
+                        vertex.tex_coords = tex_coords;
+                    }
+                }
+
+                let mut indices: Option<Vec<GLuint>> = reader.read_indices()
+                    .map(|indices| indices.into_u32().collect());
+                if let Some(&index) = indices.iter().flatten().find(|&&index| index as usize >= vertices.len()) {
+                    return Err(SceneError::InvalidIndex { mesh: mesh.index(), index, vertices: vertices.len() });
+                }
+                let mode = primitive.mode().as_gl_enum();
+
+                match reader.read_normals() {
+                    Some(normals) => {
+                        for (vertex, normal) in vertices.iter_mut().zip(normals) {
+                            vertex.normal = normal;
+                        }
+                    },
+                    None if mode == gl::TRIANGLES => {
+                        vertices = flat_shaded(&vertices, indices.take().as_deref());
+                    },
+                    None => {},
+                }
+
+                let gpu_mesh = match indices {
+                    Some(ref indices) => Mesh::with_indices(&vertices, indices),
+                    None => Mesh::new(&vertices),
+                };
+                primitives.push(Primitive {
+                    mesh: gpu_mesh.primitive(mode),
+                    material: primitive.material().index(),
+                });
+            }
+
+            meshes.push(SceneMesh {
+                name: mesh.name().map(str::to_string),
+                primitives,
+            });
+        }
+
+        let nodes: Vec<Node> = document.nodes()
+            .map(|node| Node {
+                name: node.name().map(str::to_string),
+                transform: node.transform().matrix().into(),
+                children: node.children().map(|child| child.index()).collect(),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                camera: node.camera().map(|camera| camera.index()),
+            })
+            .collect();
+        check_hierarchy(&nodes)?;
+
+        // Documents without scenes are libraries of meshes; show every root
+        let roots = match document.default_scene().or_else(|| document.scenes().next()) {
+            Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+            None => root_nodes(&document),
+        };
+
+        Ok(Scene {
+            nodes,
+            roots,
+            meshes,
+            materials: document.materials().map(PbrMaterial::from).collect(),
+            textures,
+            cameras: document.cameras().map(SceneCamera::from).collect(),
+        })
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// The top level nodes of the scene.
+    pub fn roots(&self) -> &[usize] {
+        &self.roots
+    }
+
+    pub fn meshes(&self) -> &[SceneMesh] {
+        &self.meshes
+    }
+
+    pub fn materials(&self) -> &[PbrMaterial] {
+        &self.materials
+    }
+
+    pub fn textures(&self) -> &[Texture2D] {
+        &self.textures
+    }
+
+    pub fn cameras(&self) -> &[SceneCamera] {
+        &self.cameras
+    }
+
+    /// The world transform of every node, indexed like `nodes()`. Nodes
+    /// outside the scene keep their local transform.
+    pub fn world_transforms(&self) -> Vec<Matrix4<f32>> {
+        world_transforms(&self.nodes, &self.roots)
+    }
+
+    /// The first node with a camera and its world transform.
+    pub fn camera_node(&self) -> Option<(&SceneCamera, Matrix4<f32>)> {
+        let transforms = self.world_transforms();
+        self.nodes.iter()
+            .enumerate()
+            .filter_map(|(index, node)| node.camera.map(|camera| (&self.cameras[camera], transforms[index])))
+            .next()
+    }
+
+    /// Draws every mesh of the scene, calling `setup` with the world
+    /// transform and material first so it can set the model matrix, bind
+    /// textures and set material uniforms.
+    pub fn draw_with<F: FnMut(&Matrix4<f32>, &PbrMaterial)>(&self, mut setup: F) {
+        let default_material = PbrMaterial::default();
+        let transforms = self.world_transforms();
+
+        let mut stack: Vec<usize> = self.roots.iter().rev().cloned().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if let Some(mesh) = node.mesh {
+                for primitive in &self.meshes[mesh].primitives {
+                    let material = primitive.material.map_or(&default_material, |material| &self.materials[material]);
+                    setup(&transforms[index], material);
+                    primitive.mesh.draw();
+                }
+            }
+            stack.extend(node.children.iter().rev());
+        }
+    }
+}
+
+fn root_nodes(document: &gltf::Document) -> Vec<usize> {
+    let mut is_child = vec![false; document.nodes().len()];
+    for node in document.nodes() {
+        for child in node.children() {
+            is_child[child.index()] = true;
+        }
+    }
+    (0..is_child.len()).filter(|&index| !is_child[index]).collect()
+}
+
+fn world_transforms(nodes: &[Node], roots: &[usize]) -> Vec<Matrix4<f32>> {
+    let mut transforms: Vec<Matrix4<f32>> = nodes.iter().map(|node| node.transform).collect();
+
+    let mut stack: Vec<(usize, Matrix4<f32>)> = roots.iter().map(|&root| (root, Matrix4::identity())).collect();
+    while let Some((index, parent)) = stack.pop() {
+        let world = parent * nodes[index].transform;
+        transforms[index] = world;
+        stack.extend(nodes[index].children.iter().map(|&child| (child, world)));
+    }
+
+    transforms
+}
+
+/// glTF node hierarchies are disjoint trees. Traversals of the scene rely on
+/// that, so a node with two parents or on a cycle is an error.
+fn check_hierarchy(nodes: &[Node]) -> Result<(), SceneError> {
+    let mut has_parent = vec![false; nodes.len()];
+    for node in nodes {
+        for &child in &node.children {
+            if has_parent[child] {
+                return Err(SceneError::Hierarchy { node: child, message: "has more than one parent".to_string() });
+            }
+            has_parent[child] = true;
+        }
+    }
+
+    // With one parent at most, the nodes the roots don't reach are on cycles
+    let mut reached = vec![false; nodes.len()];
+    let mut stack: Vec<usize> = (0..nodes.len()).filter(|&index| !has_parent[index]).collect();
+    while let Some(index) = stack.pop() {
+        reached[index] = true;
+        stack.extend(&nodes[index].children);
+    }
+    match reached.iter().position(|&reached| !reached) {
+        Some(node) => Err(SceneError::Hierarchy { node, message: "is its own ancestor".to_string() }),
+        None => Ok(()),
+    }
+}
+
+// glTF requires flat normals when they are missing, so every triangle gets
+// its own vertices with the face normal
+fn flat_shaded(vertices: &[ModelVertex], indices: Option<&[GLuint]>) -> Vec<ModelVertex> {
+    let mut flat: Vec<ModelVertex> = match indices {
+        Some(indices) => indices.iter().map(|&index| vertices[index as usize]).collect(),
+        None => vertices.to_vec(),
+    };
+    // A trailing partial triangle is not drawn
+    let triangles = flat.len() / 3 * 3;
+    flat.truncate(triangles);
+
+    for triangle in flat.chunks_mut(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(triangle[i].position));
+        let face = (b - a).cross(c - a);
+        if face.magnitude2() > 0.0 {
+            let normal: [f32; 3] = face.normalize().into();
+            for vertex in triangle {
+                vertex.normal = normal;
+            }
+        }
+    }
+
+    flat
+}
+
+// Color textures stay in the linear color space: the examples write shader
+// output to the framebuffer without gamma correction
+fn sampler_options(sampler: &gltf::texture::Sampler) -> TextureBuilder {
+    use gltf::texture::{MagFilter as GltfMag, MinFilter as GltfMin, WrappingMode};
+
+    let wrap = |mode| match mode {
+        WrappingMode::ClampToEdge => Wrap::ClampToEdge,
+        WrappingMode::MirroredRepeat => Wrap::MirroredRepeat,
+        WrappingMode::Repeat => Wrap::Repeat,
+    };
+    let min_filter = match sampler.min_filter() {
+        Some(GltfMin::Nearest) => MinFilter::Nearest,
+        Some(GltfMin::Linear) => MinFilter::Linear,
+        Some(GltfMin::NearestMipmapNearest) => MinFilter::NearestMipmapNearest,
+        Some(GltfMin::LinearMipmapNearest) => MinFilter::LinearMipmapNearest,
+        Some(GltfMin::NearestMipmapLinear) => MinFilter::NearestMipmapLinear,
+        Some(GltfMin::LinearMipmapLinear) | None => MinFilter::LinearMipmapLinear,
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(GltfMag::Nearest) => Filter::Nearest,
+        Some(GltfMag::Linear) | None => Filter::Linear,
+    };
+
+    Texture2D::builder()
+        .wrap_s(wrap(sampler.wrap_s()))
+        .wrap_t(wrap(sampler.wrap_t()))
+        .min_filter(min_filter)
+        .mag_filter(mag_filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::Vector4;
+
+    fn node(transform: Matrix4<f32>, children: Vec<usize>) -> Node {
+        Node {
+            name: None,
+            transform,
+            children,
+            mesh: None,
+            camera: None,
+        }
+    }
+
+    #[test]
+    fn world_transforms_compose_parent_first() {
+        let nodes = vec![
+            node(Matrix4::from_translation(Vector3::new(1.0, 0.0, 0.0)), vec![1]),
+            node(Matrix4::from_scale(2.0), vec![2]),
+            node(Matrix4::from_translation(Vector3::new(0.0, 1.0, 0.0)), vec![]),
+        ];
+
+        let transforms = world_transforms(&nodes, &[0]);
+        let origin = transforms[2] * Vector4::new(0.0, 0.0, 0.0, 1.0);
+        assert_eq!(origin, Vector4::new(1.0, 2.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn missing_normals_follow_the_winding() {
+        let vertex = |x, y| ModelVertex { position: [x, y, 0.0], normal: [0.0; 3], tex_coords: [0.0; 2] };
+        let vertices = flat_shaded(&[vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)], None);
+
+        assert_eq!(vertices.len(), 3);
+        assert!(vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn missing_normals_are_flat_across_shared_vertices() {
+        let vertex = |x, y, z| ModelVertex { position: [x, y, z], normal: [0.0; 3], tex_coords: [0.0; 2] };
+        // Two faces of a cube meeting at the edge from (0,0,0) to (0,1,0)
+        let vertices = [vertex(0.0, 0.0, 0.0), vertex(0.0, 1.0, 0.0), vertex(1.0, 0.0, 0.0), vertex(0.0, 0.0, -1.0)];
+
+        let flat = flat_shaded(&vertices, Some(&[0, 2, 1, 0, 1, 3]));
+
+        assert_eq!(flat.len(), 6);
+        assert!(flat[..3].iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+        assert!(flat[3..].iter().all(|vertex| vertex.normal == [-1.0, 0.0, 0.0]));
+    }
+
+    #[test]
+    fn node_cycles_are_errors() {
+        let identity = Matrix4::identity();
+
+        let cycle = vec![node(identity, vec![1]), node(identity, vec![2]), node(identity, vec![1])];
+        match check_hierarchy(&cycle) {
+            Err(SceneError::Hierarchy { node: 1, .. }) => {},
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let detached_cycle = vec![node(identity, vec![]), node(identity, vec![2]), node(identity, vec![1])];
+        assert!(check_hierarchy(&detached_cycle).is_err());
+
+        let tree = vec![node(identity, vec![1, 2]), node(identity, vec![]), node(identity, vec![])];
+        assert!(check_hierarchy(&tree).is_ok());
+    }
+
+    #[test]
+    fn truncated_buffer_views_are_errors() {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "buffers": [{"byteLength": 36}],
+            "bufferViews": [{"buffer": 0, "byteOffset": 30, "byteLength": 100}],
+            "images": [{"bufferView": 0, "mimeType": "image/png"}]
+        }"#;
+
+        match Scene::from_slice(&glb(json, &[0; 36]), Path::new("")) {
+            Err(SceneError::BufferView { index: 0 }) => {},
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+
+        let mut glb = Vec::new();
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        glb.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(bin);
+        glb
+    }
+
+    // A binary glTF with one triangle in its BIN chunk
+    fn triangle_glb() -> Vec<u8> {
+        let json = r#"{
+            "asset": {"version": "2.0"},
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0, "translation": [0, 0, -1]}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+            "buffers": [{"byteLength": 36}],
+            "bufferViews": [{"buffer": 0, "byteLength": 36}],
+            "accessors": [{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                           "min": [0, 0, 0], "max": [1, 1, 0]}]
+        }"#;
+
+        let mut bin = Vec::new();
+        for &component in &[0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bin.extend_from_slice(&component.to_le_bytes());
+        }
+        glb(json, &bin)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn binary_gltf_is_loaded() {
+        use headless::HeadlessContext;
+
+        // Fails rather than passing untested where there is no EGL
+        let _context = HeadlessContext::new(1, 1)
+            .unwrap_or_else(|err| panic!("no headless GL context ({}); this test needs Mesa's EGL", err));
+
+        let scene = Scene::from_slice(&triangle_glb(), Path::new("")).unwrap();
+
+        assert_eq!(scene.roots(), &[0]);
+        assert_eq!(scene.meshes()[0].primitives.len(), 1);
+        assert_eq!(scene.meshes()[0].primitives[0].mesh.count(), 3);
+        assert_eq!(scene.world_transforms()[0], Matrix4::from_translation(Vector3::new(0.0, 0.0, -1.0)));
+        assert!(scene.camera_node().is_none());
+    }
+}
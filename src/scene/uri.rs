@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::Path;
+
+use base64;
+
+use super::SceneError;
+
+/// Reads a buffer or image URI: either a base64 `data:` URI or a path
+/// relative to the directory of the glTF file.
+pub fn read_uri(uri: &str, base: &Path) -> Result<Vec<u8>, SceneError> {
+    if uri.starts_with("data:") {
+        return decode_data_uri(uri);
+    }
+
+    let path = base.join(percent_decode(uri));
+    fs::read(&path).map_err(|error| SceneError::Io { path, error })
+}
+
+// `data:[<media type>];base64,<data>`
+fn decode_data_uri(uri: &str) -> Result<Vec<u8>, SceneError> {
+    let comma = match uri.find(',') {
+        Some(comma) => comma,
+        None => return Err(SceneError::UnsupportedUri(uri.to_string())),
+    };
+    if !uri[..comma].ends_with(";base64") {
+        return Err(SceneError::UnsupportedUri(uri[..comma].to_string()));
+    }
+
+    Ok(base64::decode(&uri[comma + 1..])?)
+}
+
+// Relative URIs escape spaces and other reserved characters as `%XX`
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = if bytes[i] == b'%' && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit() {
+            u8::from_str_radix(&uri[i + 1..i + 3], 16).ok()
+        } else {
+            None
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_uris_are_decoded() {
+        let bytes = read_uri("data:application/octet-stream;base64,AAECAw==", Path::new("")).unwrap();
+        assert_eq!(bytes, vec![0, 1, 2, 3]);
+
+        match read_uri("data:text/plain,hello", Path::new("")) {
+            Err(SceneError::UnsupportedUri(_)) => {},
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn escaped_characters_are_decoded() {
+        assert_eq!(percent_decode("my%20scene%2Fbuffer.bin"), "my scene/buffer.bin");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
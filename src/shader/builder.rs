@@ -0,0 +1,216 @@
+use std::path::{Path, PathBuf};
+
+use super::{Shader, ShaderError, ShaderStage, StageSource};
+
+/// Collects the sources for any combination of graphics pipeline stages, as
+/// paths or as text embedded in the binary.
+///
+/// ```no_run
+/// # use opengl_sdl2_demo::shader::ShaderBuilder;
+/// let shader = ShaderBuilder::new()
+///     .vertex("shader/terrain.glslv")
+///     .tess_control("shader/terrain.glsltc")
+///     .tess_evaluation("shader/terrain.glslte")
+///     .fragment("shader/terrain.glslf")
+///     .define("MAX_LIGHTS", "4")
+///     .build();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ShaderBuilder {
+    stages: Vec<(ShaderStage, StageSource)>,
+    defines: Vec<(String, String)>,
+}
+
+impl ShaderBuilder {
+    pub fn new() -> ShaderBuilder {
+        ShaderBuilder::default()
+    }
+
+    pub fn vertex<P: AsRef<Path>>(self, path: P) -> ShaderBuilder {
+        self.stage(ShaderStage::Vertex, path)
+    }
+
+    pub fn tess_control<P: AsRef<Path>>(self, path: P) -> ShaderBuilder {
+        self.stage(ShaderStage::TessControl, path)
+    }
+
+    pub fn tess_evaluation<P: AsRef<Path>>(self, path: P) -> ShaderBuilder {
+        self.stage(ShaderStage::TessEvaluation, path)
+    }
+
+    pub fn geometry<P: AsRef<Path>>(self, path: P) -> ShaderBuilder {
+        self.stage(ShaderStage::Geometry, path)
+    }
+
+    pub fn fragment<P: AsRef<Path>>(self, path: P) -> ShaderBuilder {
+        self.stage(ShaderStage::Fragment, path)
+    }
+
+    /// Sets the source for `stage`, replacing any previous source for it.
+    pub fn stage<P: AsRef<Path>>(self, stage: ShaderStage, path: P) -> ShaderBuilder {
+        self.set(stage, StageSource::File(path.as_ref().to_path_buf()))
+    }
+
+    /// Sets `text` as the source for `stage`, e.g. GLSL embedded with
+    /// `include_str!`, so the program doesn't depend on files at run time.
+    /// `name` stands in for the file name in errors; includes are resolved
+    /// relative to its directory.
+    pub fn stage_source<P: AsRef<Path>, S: Into<String>>(self, stage: ShaderStage, name: P, text: S) -> ShaderBuilder {
+        self.set(stage, StageSource::Text { name: name.as_ref().to_path_buf(), text: text.into() })
+    }
+
+    fn set(mut self, stage: ShaderStage, source: StageSource) -> ShaderBuilder {
+        self.stages.retain(|&(existing, _)| existing != stage);
+        self.stages.push((stage, source));
+        self
+    }
+
+    /// Adds `#define name value` after the `#version` line of every stage.
+    pub fn define<S: Into<String>>(mut self, name: S, value: S) -> ShaderBuilder {
+        self.defines.push((name.into(), value.into()));
+        self
+    }
+
+    /// The files of the stages set by path.
+    pub fn paths(&self) -> Vec<&Path> {
+        self.stages.iter()
+            .filter_map(|(_, source)| match *source {
+                StageSource::File(ref path) => Some(path.as_path()),
+                StageSource::Text { .. } => None,
+            })
+            .collect()
+    }
+
+    /// The stage source files together with every file the stages include,
+    /// sorted and each listed once. Files that cannot be preprocessed
+    /// contribute only themselves. Reads every file, so callers polling for
+    /// changes should keep the result.
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for (_, source) in &self.stages {
+            match (source, source.preprocess(&self.defines)) {
+                (StageSource::File(_), Ok(preprocessed)) => files.extend(preprocessed.files().iter().cloned()),
+                // The first file of a text source is only its name
+                (StageSource::Text { .. }, Ok(preprocessed)) => files.extend(preprocessed.files()[1..].iter().cloned()),
+                (StageSource::File(path), Err(_)) => files.push(path.clone()),
+                (StageSource::Text { .. }, Err(_)) => {},
+            }
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    pub fn build(&self) -> Result<Shader, ShaderError> {
+        self.validate()?;
+        Shader::from_stages(&self.stages, &self.defines)
+    }
+
+    fn has(&self, stage: ShaderStage) -> bool {
+        self.stages.iter().any(|&(existing, _)| existing == stage)
+    }
+
+    fn validate(&self) -> Result<(), ShaderError> {
+        if self.has(ShaderStage::Compute) {
+            return Err(ShaderError::InvalidStages("compute shaders must be built with ComputeProgram"));
+        }
+        if !self.has(ShaderStage::Vertex) {
+            return Err(ShaderError::InvalidStages("a vertex stage is required"));
+        }
+        if self.has(ShaderStage::TessControl) && !self.has(ShaderStage::TessEvaluation) {
+            return Err(ShaderError::InvalidStages("a tessellation control stage requires a tessellation evaluation stage"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn dependencies_list_shared_includes_once() {
+        let dir = env::temp_dir().join("opengl_sdl2_demo_dependencies");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("common.glsl"), "uniform float time;\n").unwrap();
+        fs::write(dir.join("main.glslv"), "#version 330 core\n#include \"common.glsl\"\nvoid main() {}\n").unwrap();
+        fs::write(dir.join("main.glslf"), "#version 330 core\n#include \"common.glsl\"\nvoid main() {}\n").unwrap();
+
+        let builder = ShaderBuilder::new()
+            .vertex(dir.join("main.glslv"))
+            .fragment(dir.join("main.glslf"));
+
+        assert_eq!(builder.dependencies(), vec![dir.join("common.glsl"), dir.join("main.glslf"), dir.join("main.glslv")]);
+    }
+
+    #[test]
+    fn text_sources_depend_only_on_their_includes() {
+        let dir = env::temp_dir().join("opengl_sdl2_demo_text_dependencies");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("common.glsl"), "uniform float time;\n").unwrap();
+        fs::write(dir.join("main.glslf"), "#version 330 core\nvoid main() {}\n").unwrap();
+
+        let builder = ShaderBuilder::new()
+            .stage_source(ShaderStage::Vertex, dir.join("inline.glslv"), "#version 330 core\n#include \"common.glsl\"\nvoid main() {}\n")
+            .fragment(dir.join("main.glslf"));
+
+        assert_eq!(builder.paths(), vec![dir.join("main.glslf").as_path()]);
+        assert_eq!(builder.dependencies(), vec![dir.join("common.glsl"), dir.join("main.glslf")]);
+    }
+
+    fn invalid_stages(builder: ShaderBuilder) -> &'static str {
+        match builder.build() {
+            Err(ShaderError::InvalidStages(reason)) => reason,
+            other => panic!("expected invalid stages, got {:?}", other),
+        }
+    }
+
+    // Checked before anything is compiled, so no GL context is needed
+    #[test]
+    fn invalid_stage_combinations_are_rejected() {
+        let vertex = ShaderBuilder::new().vertex("main.glslv").fragment("main.glslf");
+
+        assert!(invalid_stages(vertex.clone().stage(ShaderStage::Compute, "main.glslc")).contains("ComputeProgram"));
+        assert!(invalid_stages(ShaderBuilder::new().fragment("main.glslf")).contains("vertex"));
+        assert!(invalid_stages(vertex.tess_control("main.glsltc")).contains("tessellation evaluation"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn geometry_stage_is_linked() {
+        use headless::HeadlessContext;
+
+        // Fails rather than passing untested where there is no EGL
+        let _context = HeadlessContext::new(1, 1)
+            .unwrap_or_else(|err| panic!("no headless GL context ({}); this test needs Mesa's EGL", err));
+
+        let shader = ShaderBuilder::new()
+            .stage_source(ShaderStage::Vertex, "pass.glslv", "#version 330 core
+layout (location = 0) in vec3 position;
+void main() { gl_Position = vec4(position, 1.0); }
+")
+            .stage_source(ShaderStage::Geometry, "pass.glslg", "#version 330 core
+layout (triangles) in;
+layout (triangle_strip, max_vertices = 3) out;
+void main() {
+    for (int i = 0; i < 3; i++) {
+        gl_Position = gl_in[i].gl_Position;
+        EmitVertex();
+    }
+    EndPrimitive();
+}
+")
+            .stage_source(ShaderStage::Fragment, "pass.glslf", "#version 330 core
+out vec4 color;
+void main() { color = vec4(1.0); }
+")
+            .build()
+            .unwrap();
+
+        assert_ne!(shader.program(), 0);
+    }
+}
@@ -0,0 +1,168 @@
+use std::ops::BitOr;
+use std::path::Path;
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+use super::{Shader, ShaderError, ShaderStage, StageSource};
+
+/// Bits for `glMemoryBarrier`, combinable with `|`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MemoryBarrier(pub GLbitfield);
+
+impl MemoryBarrier {
+    pub const VERTEX_ATTRIB_ARRAY: MemoryBarrier = MemoryBarrier(gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+    pub const ELEMENT_ARRAY: MemoryBarrier = MemoryBarrier(gl::ELEMENT_ARRAY_BARRIER_BIT);
+    pub const UNIFORM: MemoryBarrier = MemoryBarrier(gl::UNIFORM_BARRIER_BIT);
+    pub const TEXTURE_FETCH: MemoryBarrier = MemoryBarrier(gl::TEXTURE_FETCH_BARRIER_BIT);
+    pub const SHADER_IMAGE_ACCESS: MemoryBarrier = MemoryBarrier(gl::SHADER_IMAGE_ACCESS_BARRIER_BIT);
+    pub const COMMAND: MemoryBarrier = MemoryBarrier(gl::COMMAND_BARRIER_BIT);
+    pub const BUFFER_UPDATE: MemoryBarrier = MemoryBarrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    pub const TEXTURE_UPDATE: MemoryBarrier = MemoryBarrier(gl::TEXTURE_UPDATE_BARRIER_BIT);
+    pub const FRAMEBUFFER: MemoryBarrier = MemoryBarrier(gl::FRAMEBUFFER_BARRIER_BIT);
+    pub const SHADER_STORAGE: MemoryBarrier = MemoryBarrier(gl::SHADER_STORAGE_BARRIER_BIT);
+    pub const ALL: MemoryBarrier = MemoryBarrier(gl::ALL_BARRIER_BITS);
+
+    /// Issues `glMemoryBarrier` for these bits.
+    pub fn wait(self) {
+        unsafe {
+            gl::MemoryBarrier(self.0);
+        }
+    }
+}
+
+impl BitOr for MemoryBarrier {
+    type Output = MemoryBarrier;
+
+    fn bitor(self, rhs: MemoryBarrier) -> MemoryBarrier {
+        MemoryBarrier(self.0 | rhs.0)
+    }
+}
+
+/// A program made of a single compute stage.
+#[derive(Debug)]
+pub struct ComputeProgram {
+    shader: Shader,
+}
+
+impl ComputeProgram {
+    pub fn from_source<P: AsRef<Path>>(compute: P) -> Result<ComputeProgram, ShaderError> {
+        Self::with_defines(compute, &[])
+    }
+
+    /// Compiles `compute` with `defines` injected after its `#version` line.
+    pub fn with_defines<P: AsRef<Path>>(compute: P, defines: &[(String, String)]) -> Result<ComputeProgram, ShaderError> {
+        let source = StageSource::File(compute.as_ref().to_path_buf());
+        // Every compute shader declares its work group size; other stages don't
+        if !declares_work_group_size(&source.preprocess(defines)?.text) {
+            return Err(ShaderError::InvalidStages("a compute shader must declare layout (local_size_x = ...) in"));
+        }
+        let shader = Shader::from_stages(&[(ShaderStage::Compute, source)], defines)?;
+
+        Ok(ComputeProgram {
+            shader,
+        })
+    }
+
+    /// The underlying program, for uniform setters and reflection.
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn program(&self) -> GLuint {
+        self.shader.program()
+    }
+
+    /// The `local_size_x/y/z` declared by the shader.
+    pub fn work_group_size(&self) -> [GLint; 3] {
+        let mut size = [0; 3];
+        unsafe {
+            gl::GetProgramiv(self.shader.program(), gl::COMPUTE_WORK_GROUP_SIZE, size.as_mut_ptr());
+        }
+        size
+    }
+
+    /// Binds the program and launches `x * y * z` work groups.
+    pub fn dispatch(&self, x: GLuint, y: GLuint, z: GLuint) {
+        self.shader.gl_use();
+        unsafe {
+            gl::DispatchCompute(x, y, z);
+        }
+    }
+
+    /// Like `dispatch`, followed by a `glMemoryBarrier` so later commands see the writes.
+    pub fn dispatch_with_barrier(&self, x: GLuint, y: GLuint, z: GLuint, barrier: MemoryBarrier) {
+        self.dispatch(x, y, z);
+        barrier.wait();
+    }
+}
+
+fn declares_work_group_size(source: &str) -> bool {
+    source.lines()
+        .map(|line| line.split("//").next().unwrap_or(""))
+        .any(|line| line.contains("local_size_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn write(name: &str, source: &str) -> PathBuf {
+        let dir = env::temp_dir().join("opengl_sdl2_demo_compute");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    #[test]
+    fn barriers_combine_their_bits() {
+        let barrier = MemoryBarrier::SHADER_STORAGE | MemoryBarrier::BUFFER_UPDATE | MemoryBarrier::SHADER_STORAGE;
+
+        assert_eq!(barrier, MemoryBarrier(gl::SHADER_STORAGE_BARRIER_BIT | gl::BUFFER_UPDATE_BARRIER_BIT));
+    }
+
+    #[test]
+    fn other_stages_are_not_compute_programs() {
+        let vertex = write("vertex.glslv", "#version 430 core\n// not a local_size_x declaration\nvoid main() { gl_Position = vec4(0.0); }\n");
+
+        match ComputeProgram::from_source(vertex) {
+            Err(ShaderError::InvalidStages(_)) => {},
+            other => panic!("expected invalid stages, got {:?}", other),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dispatch_writes_a_storage_buffer() {
+        use headless::HeadlessContext;
+        use object::{Buffer, BufferTarget};
+        use std::mem;
+
+        // Fails rather than passing untested where there is no EGL
+        let _context = HeadlessContext::new(1, 1)
+            .unwrap_or_else(|err| panic!("no headless GL context ({}); this test needs Mesa's EGL", err));
+
+        let squares = write("squares.glslc", "#version 430 core
+layout (local_size_x = 4) in;
+layout (std430, binding = 0) buffer Values { uint values[]; };
+void main() { uint i = gl_GlobalInvocationID.x; values[i] = i * i; }
+");
+        let program = ComputeProgram::from_source(squares).unwrap();
+        assert_eq!(program.work_group_size(), [4, 1, 1]);
+
+        let buffer = Buffer::new(BufferTarget::ShaderStorage);
+        buffer.data(&[0u32; 8], gl::DYNAMIC_READ);
+        let mut values = [0u32; 8];
+        unsafe {
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, buffer.id());
+            program.dispatch_with_barrier(2, 1, 1, MemoryBarrier::BUFFER_UPDATE);
+            gl::GetBufferSubData(gl::SHADER_STORAGE_BUFFER, 0, mem::size_of_val(&values) as GLsizeiptr, values.as_mut_ptr() as *mut _);
+        }
+
+        assert_eq!(values, [0, 1, 4, 9, 16, 25, 36, 49]);
+    }
+}
@@ -0,0 +1,219 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::{self, Utf8Error};
+
+use super::ShaderStage;
+
+/// One entry of a driver info log, with the source line it refers to when the
+/// driver reported one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogLine {
+    /// The included file the line belongs to; `None` for the stage's own file.
+    pub file: Option<PathBuf>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl LogLine {
+    /// Parses the common driver formats:
+    ///
+    /// * Mesa:   `0:12(5): error: ...`
+    /// * NVIDIA: `0(12) : error C0000: ...`
+    /// * AMD:    `ERROR: 0:12: ...`
+    pub fn parse(raw: &str) -> LogLine {
+        let trimmed = raw.trim();
+
+        let (prefix, rest) = if trimmed.starts_with("ERROR: ") || trimmed.starts_with("WARNING: ") {
+            let split = trimmed.find(' ').unwrap();
+            (&trimmed[..split], trimmed[split + 1..].trim_start())
+        } else {
+            ("", trimmed)
+        };
+
+        let position = Self::parse_mesa(rest)
+            .or_else(|| Self::parse_nvidia(rest))
+            .or_else(|| Self::parse_amd(rest));
+
+        match position {
+            Some((line, message)) => {
+                let message = if prefix.is_empty() {
+                    message.to_string()
+                } else {
+                    format!("{} {}", prefix.to_lowercase(), message)
+                };
+                LogLine { file: None, line: Some(line), message }
+            },
+            None => LogLine { file: None, line: None, message: trimmed.to_string() },
+        }
+    }
+
+    pub fn parse_log(log: &str) -> Vec<LogLine> {
+        log.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(LogLine::parse)
+            .collect()
+    }
+
+    // `<file>:<line>(<column>): <message>`
+    fn parse_mesa(s: &str) -> Option<(u32, &str)> {
+        let colon = s.find(':')?;
+        Self::leading_number(&s[..colon])?;
+        let rest = &s[colon + 1..];
+        let paren = rest.find('(')?;
+        let line = Self::leading_number(&rest[..paren])?;
+        let close = rest.find("):")?;
+        Some((line, rest[close + 2..].trim()))
+    }
+
+    // `<file>(<line>) : <message>`
+    fn parse_nvidia(s: &str) -> Option<(u32, &str)> {
+        let paren = s.find('(')?;
+        Self::leading_number(&s[..paren])?;
+        let close = s.find(')')?;
+        let line = Self::leading_number(&s[paren + 1..close])?;
+        let rest = s[close + 1..].trim_start();
+        if !rest.starts_with(':') {
+            return None;
+        }
+        Some((line, rest[1..].trim()))
+    }
+
+    // `<file>:<line>: <message>`
+    fn parse_amd(s: &str) -> Option<(u32, &str)> {
+        let mut parts = s.splitn(3, ':');
+        Self::leading_number(parts.next()?)?;
+        let line = Self::leading_number(parts.next()?)?;
+        Some((line, parts.next()?.trim()))
+    }
+
+    fn leading_number(s: &str) -> Option<u32> {
+        let s = s.trim();
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    }
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    /// A shader source file could not be opened or read.
+    Io { path: PathBuf, error: io::Error },
+    /// A stage failed to compile; `log` holds the parsed info log.
+    Compile { stage: ShaderStage, path: PathBuf, log: Vec<LogLine> },
+    /// The program failed to link.
+    Link { log: String },
+    /// The requested combination of stages cannot form a program.
+    InvalidStages(&'static str),
+    /// A file includes itself, directly or through other includes.
+    IncludeCycle { chain: Vec<PathBuf> },
+    /// A malformed preprocessor directive.
+    Preprocess { path: PathBuf, line: u32, message: String },
+    /// The driver returned an info log that was not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+}
+
+impl ShaderError {
+    pub(crate) fn info_log(buf: &[u8]) -> Result<String, ShaderError> {
+        str::from_utf8(buf)
+            .map(|log| log.to_string())
+            .map_err(ShaderError::InvalidUtf8)
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ShaderError::Io { ref path, ref error } => {
+                write!(f, "{}: {}", path.display(), error)
+            },
+            ShaderError::Compile { stage, ref path, ref log } => {
+                write!(f, "{}: {} shader failed to compile", path.display(), stage)?;
+                for entry in log {
+                    let file = entry.file.as_ref().unwrap_or(path);
+                    match entry.line {
+                        Some(line) => write!(f, "\n{}:{}: {}", file.display(), line, entry.message)?,
+                        None => write!(f, "\n{}: {}", file.display(), entry.message)?,
+                    }
+                }
+                Ok(())
+            },
+            ShaderError::Link { ref log } => {
+                write!(f, "program failed to link: {}", log.trim())
+            },
+            ShaderError::InvalidStages(reason) => {
+                write!(f, "invalid shader stages: {}", reason)
+            },
+            ShaderError::IncludeCycle { ref chain } => {
+                let chain: Vec<String> = chain.iter().map(|path| path.display().to_string()).collect();
+                write!(f, "include cycle: {}", chain.join(" -> "))
+            },
+            ShaderError::Preprocess { ref path, line, ref message } => {
+                write!(f, "{}:{}: {}", path.display(), line, message)
+            },
+            ShaderError::InvalidUtf8(ref error) => {
+                write!(f, "info log is not valid utf8: {}", error)
+            },
+        }
+    }
+}
+
+impl Error for ShaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            ShaderError::Io { ref error, .. } => Some(error),
+            ShaderError::InvalidUtf8(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mesa_log_line() {
+        let line = LogLine::parse("0:12(5): error: `color' undeclared");
+
+        assert_eq!(line.line, Some(12));
+        assert_eq!(line.message, "error: `color' undeclared");
+        assert_eq!(line.file, None);
+    }
+
+    #[test]
+    fn nvidia_log_line() {
+        let line = LogLine::parse("0(7) : error C1008: undefined variable \"color\"");
+
+        assert_eq!(line.line, Some(7));
+        assert_eq!(line.message, "error C1008: undefined variable \"color\"");
+    }
+
+    #[test]
+    fn amd_log_line() {
+        let line = LogLine::parse("ERROR: 0:23: 'color' : undeclared identifier");
+        assert_eq!(line.line, Some(23));
+        assert_eq!(line.message, "error: 'color' : undeclared identifier");
+
+        let line = LogLine::parse("WARNING: 0:3: extension not supported");
+        assert_eq!(line.line, Some(3));
+        assert_eq!(line.message, "warning: extension not supported");
+    }
+
+    #[test]
+    fn unparseable_lines_are_kept_whole() {
+        let line = LogLine::parse("  Compilation failed due to 2 errors.  ");
+
+        assert_eq!(line.line, None);
+        assert_eq!(line.message, "Compilation failed due to 2 errors.");
+    }
+
+    #[test]
+    fn parse_log_skips_blank_lines() {
+        let log = LogLine::parse_log("0:1(1): error: first\n\n0:2(3): error: second\n");
+
+        assert_eq!(log.iter().map(|line| line.line).collect::<Vec<_>>(), vec![Some(1), Some(2)]);
+    }
+}
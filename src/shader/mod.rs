@@ -1,66 +1,155 @@
 use std::ffi::CString;
-use std::fs::File;
-use std::io::BufReader;
-use std::io::prelude::*;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use std::ptr;
-use std::str;
 
+use context::{ContextId, GlObject};
 use gl_gen::gl;
 use gl_gen::gl::types::*;
 
-#[derive(Copy, Clone, Debug)]
+mod builder;
+mod compute;
+mod error;
+mod preprocess;
+mod reflect;
+mod reload;
+mod uniform;
+pub use self::builder::ShaderBuilder;
+pub use self::compute::{ComputeProgram, MemoryBarrier};
+pub use self::error::{LogLine, ShaderError};
+pub use self::preprocess::{preprocess, preprocess_str, Source};
+pub use self::reflect::{AttributeInfo, LayoutMismatch, ProgramReflection, UniformBlockInfo, UniformInfo};
+pub use self::reflect::{component_count, is_sampler};
+pub use self::reload::ReloadableShader;
+pub use self::uniform::UniformCache;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    TessControl,
+    TessEvaluation,
+    Geometry,
+    Fragment,
+    Compute,
+}
+
+impl ShaderStage {
+    pub fn gl_enum(self) -> GLenum {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::TessControl => gl::TESS_CONTROL_SHADER,
+            ShaderStage::TessEvaluation => gl::TESS_EVALUATION_SHADER,
+            ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+            ShaderStage::Compute => gl::COMPUTE_SHADER,
+        }
+    }
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            ShaderStage::Vertex => "vertex",
+            ShaderStage::TessControl => "tessellation control",
+            ShaderStage::TessEvaluation => "tessellation evaluation",
+            ShaderStage::Geometry => "geometry",
+            ShaderStage::Fragment => "fragment",
+            ShaderStage::Compute => "compute",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Where the GLSL of a stage comes from.
+#[derive(Clone, Debug)]
+enum StageSource {
+    /// A file, read whenever the program is built.
+    File(PathBuf),
+    /// Source text, e.g. embedded with `include_str!`; `name` stands in for
+    /// its file name.
+    Text { name: PathBuf, text: String },
+}
+
+impl StageSource {
+    /// The file name, or the name standing in for one.
+    fn path(&self) -> &Path {
+        match *self {
+            StageSource::File(ref path) => path,
+            StageSource::Text { ref name, .. } => name,
+        }
+    }
+
+    fn preprocess(&self, defines: &[(String, String)]) -> Result<Source, ShaderError> {
+        match *self {
+            StageSource::File(ref path) => preprocess(path, defines),
+            StageSource::Text { ref name, ref text } => preprocess_str(name, text, defines),
+        }
+    }
+}
+
+/// An owned, linked program. The program is deleted on drop.
+#[derive(Debug)]
 pub struct Shader {
     program: GLuint,
+    uniforms: UniformCache,
+    context: ContextId,
 }
 
 impl Shader {
-    pub fn from_source<'a>(vertex: &'a str, fragment: &'a str) -> Shader {
-        println!("{:?}", vertex);
-        let vertex_glsl = File::open(vertex).expect("Could not open vertex path");
-        let fragment_glsl = File::open(fragment).expect("Could not open fragment path");
-        
-        let mut buf_reader = BufReader::new(vertex_glsl);
-        let mut vertex_shader = String::new();
-        buf_reader.read_to_string(&mut vertex_shader).expect("Could not read vertex path");;
-
-        let mut buf_reader = BufReader::new(fragment_glsl);
-        let mut fragment_shader = String::new();
-        buf_reader.read_to_string(&mut fragment_shader).expect("Could not read fragment path");
+    /// Builds a vertex + fragment program. Use `ShaderBuilder` for the other stages.
+    pub fn from_source<P: AsRef<Path>>(vertex: P, fragment: P) -> Result<Shader, ShaderError> {
+        ShaderBuilder::new()
+            .vertex(vertex)
+            .fragment(fragment)
+            .build()
+    }
 
-        // Create GLSL shaders
-        let vs = Self::compile_shader(vertex_shader.as_str(), gl::VERTEX_SHADER);
-        let fs = Self::compile_shader(fragment_shader.as_str(), gl::FRAGMENT_SHADER);
+    /// Preprocesses and compiles every stage and links them into one program.
+    fn from_stages(stages: &[(ShaderStage, StageSource)], defines: &[(String, String)]) -> Result<Shader, ShaderError> {
+        let context = ContextId::current();
+        let mut shaders = Vec::with_capacity(stages.len());
 
-        let program = Self::link_program(vs, fs);
+        let mut compile_and_link = || {
+            for (stage, source) in stages {
+                let preprocessed = source.preprocess(defines)?;
+                shaders.push(Self::compile_shader(&preprocessed, *stage, source.path())?);
+            }
+            Self::link_program(&shaders)
+        };
+        let program = compile_and_link();
 
         unsafe {
-            gl::DeleteShader(fs);
-            gl::DeleteShader(vs);
+            for &shader in &shaders {
+                gl::DeleteShader(shader);
+            }
         }
 
-        Shader {
-            program,
-        }
+        Ok(Shader {
+            program: program?,
+            uniforms: UniformCache::new(),
+            context,
+        })
     }
 
-    pub fn gl_use(self) {
+    pub fn gl_use(&self) {
+        self.context.assert_alive("Shader");
         unsafe {
             gl::UseProgram(self.program);
         }
     }
 
-    pub fn program(self) -> GLuint {
+    pub fn program(&self) -> GLuint {
         self.program
     }
 
-    fn compile_shader(src: &str, ty: GLenum) -> GLuint {
+    fn compile_shader(src: &Source, stage: ShaderStage, path: &Path) -> Result<GLuint, ShaderError> {
         let shader;
 
         unsafe {
-            shader = gl::CreateShader(ty);
+            shader = gl::CreateShader(stage.gl_enum());
 
             // Attempt to compile the shader
-            let c_str = CString::new(src.as_bytes()).unwrap();
+            let c_str = CString::new(src.text.as_bytes()).unwrap();
             gl::ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null());
             gl::CompileShader(shader);
 
@@ -73,26 +162,42 @@ impl Shader {
                 let mut len = 0;
                 gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
 
-                let mut buf = Vec::with_capacity(len as usize);
-                buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
-
+                let mut buf = vec![0u8; len as usize];
                 gl::GetShaderInfoLog(shader, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
-                println!("{:?}",buf);
-
-                panic!("{}", str::from_utf8(&buf).ok().expect("ShaderInfoLog not valid utf8"));
+                buf.pop(); // skip the trailing null character
+                gl::DeleteShader(shader);
+
+                let log = ShaderError::info_log(&buf)?;
+                return Err(ShaderError::Compile {
+                    stage,
+                    path: path.to_path_buf(),
+                    log: Self::map_log_lines(LogLine::parse_log(&log), src, path),
+                });
             }
         }
-        shader
+        Ok(shader)
     }
 
-    fn link_program(vs: GLuint, fs: GLuint) -> GLuint { 
+    /// Rewrites line numbers of the preprocessed source to the original files.
+    fn map_log_lines(log: Vec<LogLine>, src: &Source, path: &Path) -> Vec<LogLine> {
+        log.into_iter().map(|mut entry| {
+            if let Some((file, line)) = entry.line.and_then(|line| src.original_line(line)) {
+                entry.file = if file == path { None } else { Some(file.to_path_buf()) };
+                entry.line = Some(line);
+            }
+            entry
+        }).collect()
+    }
+
+    fn link_program(shaders: &[GLuint]) -> Result<GLuint, ShaderError> {
         let program;
 
         unsafe {
             program = gl::CreateProgram();
 
-            gl::AttachShader(program, vs);
-            gl::AttachShader(program, fs);
+            for &shader in shaders {
+                gl::AttachShader(program, shader);
+            }
             gl::LinkProgram(program);
 
             // Get the link status
@@ -104,13 +209,20 @@ impl Shader {
                 let mut len: GLint = 0;
                 gl::GetProgramiv(program, gl::INFO_LOG_LENGTH, &mut len);
 
-                let mut buf = Vec::with_capacity(len as usize);
-                buf.set_len((len as usize) - 1); // subtract 1 to skip the trailing null character
+                let mut buf = vec![0u8; len as usize];
                 gl::GetProgramInfoLog(program, len, ptr::null_mut(), buf.as_mut_ptr() as *mut GLchar);
-                
-                panic!("{}", str::from_utf8(&buf).ok().expect("ProgramInfoLog not valid utf8"));
+                buf.pop(); // skip the trailing null character
+                gl::DeleteProgram(program);
+
+                return Err(ShaderError::Link { log: ShaderError::info_log(&buf)? });
             }
-        } 
-        program
+        }
+        Ok(program)
     }
-}
\ No newline at end of file
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        self.context.delete(GlObject::Program(self.program));
+    }
+}
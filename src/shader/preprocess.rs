@@ -0,0 +1,241 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+use super::ShaderError;
+
+/// GLSL source after `#include` resolution and `#define` injection, with a map
+/// from every output line back to the file and line it came from.
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub text: String,
+    files: Vec<PathBuf>,
+    lines: Vec<(usize, u32)>,
+}
+
+impl Source {
+    /// Every file that contributed to this source, the root file first.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Maps a 1-based line of `text` back to its original file and line.
+    pub fn original_line(&self, line: u32) -> Option<(&Path, u32)> {
+        let index = (line as usize).checked_sub(1)?;
+        self.lines.get(index).map(|&(file, line)| (self.files[file].as_path(), line))
+    }
+}
+
+/// Reads `path`, splices in `#include "file"` directives (relative to the
+/// including file) and inserts `defines` right after the `#version` line.
+pub fn preprocess(path: &Path, defines: &[(String, String)]) -> Result<Source, ShaderError> {
+    let mut preprocessor = Preprocessor::new(defines);
+    preprocessor.expand(path, &mut Vec::new())?;
+
+    Ok(preprocessor.source)
+}
+
+/// `preprocess` for source text that isn't read from a file, e.g. one embedded
+/// with `include_str!`. `name` stands in for its file name; includes are
+/// resolved relative to its directory.
+pub fn preprocess_str(name: &Path, text: &str, defines: &[(String, String)]) -> Result<Source, ShaderError> {
+    let mut preprocessor = Preprocessor::new(defines);
+    preprocessor.expand_text(name, name.to_path_buf(), text, &mut Vec::new())?;
+
+    Ok(preprocessor.source)
+}
+
+pub fn read_source(path: &Path) -> Result<String, ShaderError> {
+    let to_error = |error| ShaderError::Io { path: path.to_path_buf(), error };
+
+    let file = File::open(path).map_err(to_error)?;
+    let mut buf_reader = BufReader::new(file);
+    let mut source = String::new();
+    buf_reader.read_to_string(&mut source).map_err(to_error)?;
+
+    Ok(source)
+}
+
+struct Preprocessor<'a> {
+    defines: &'a [(String, String)],
+    source: Source,
+    defines_written: bool,
+}
+
+impl<'a> Preprocessor<'a> {
+    fn new(defines: &'a [(String, String)]) -> Preprocessor<'a> {
+        Preprocessor {
+            defines,
+            source: Source { text: String::new(), files: Vec::new(), lines: Vec::new() },
+            defines_written: false,
+        }
+    }
+
+    fn expand(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<(), ShaderError> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if stack.contains(&canonical) {
+            let mut chain = stack.clone();
+            chain.push(canonical);
+            return Err(ShaderError::IncludeCycle { chain });
+        }
+
+        let text = read_source(path)?;
+        self.expand_text(path, canonical, &text, stack)
+    }
+
+    fn expand_text(&mut self, path: &Path, canonical: PathBuf, text: &str, stack: &mut Vec<PathBuf>) -> Result<(), ShaderError> {
+        let file = self.source.files.len();
+        self.source.files.push(path.to_path_buf());
+
+        let is_root = stack.is_empty();
+        if is_root && !text.lines().any(is_version) {
+            self.write_defines(file, 1);
+        }
+
+        stack.push(canonical);
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index as u32 + 1;
+
+            match parse_include(line) {
+                Some(Ok(include)) => {
+                    let target = path.parent().unwrap_or_else(|| Path::new("")).join(include);
+                    self.expand(&target, stack)?;
+                },
+                Some(Err(message)) => {
+                    return Err(ShaderError::Preprocess {
+                        path: path.to_path_buf(),
+                        line: line_number,
+                        message,
+                    });
+                },
+                None => {
+                    self.push_line(line, file, line_number);
+                    if is_root && is_version(line) {
+                        self.write_defines(file, line_number);
+                    }
+                },
+            }
+        }
+        stack.pop();
+
+        Ok(())
+    }
+
+    fn write_defines(&mut self, file: usize, line: u32) {
+        if self.defines_written {
+            return;
+        }
+        self.defines_written = true;
+
+        for (name, value) in self.defines {
+            let define = format!("#define {} {}", name, value);
+            self.push_line(define.trim_end(), file, line);
+        }
+    }
+
+    fn push_line(&mut self, text: &str, file: usize, line: u32) {
+        self.source.text.push_str(text);
+        self.source.text.push('\n');
+        self.source.lines.push((file, line));
+    }
+}
+
+fn is_version(line: &str) -> bool {
+    line.trim_start().starts_with("#version")
+}
+
+/// `None` if `line` is not an include directive, otherwise the quoted file name
+/// or a description of what is wrong with the directive.
+fn parse_include(line: &str) -> Option<Result<&str, String>> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start().strip_prefix("include")?;
+    let rest = rest.trim();
+
+    let file = rest.strip_prefix('"')
+        .and_then(|rest| rest.find('"').map(|end| &rest[..end]))
+        .filter(|file| !file.is_empty());
+
+    Some(file.ok_or_else(|| format!("expected #include \"file\", found `{}`", line.trim())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("opengl_sdl2_demo_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn includes_are_spliced_and_mapped_back() {
+        let dir = temp_dir("include");
+        write(&dir, "common.glsl", "uniform mat4 model;\nuniform mat4 view;\n");
+        let root = write(&dir, "main.glslv", "#version 330 core\n#include \"common.glsl\"\nvoid main() {}\n");
+
+        let source = preprocess(&root, &[]).unwrap();
+
+        assert_eq!(source.text, "#version 330 core\nuniform mat4 model;\nuniform mat4 view;\nvoid main() {}\n");
+        assert_eq!(source.original_line(3), Some((dir.join("common.glsl").as_path(), 2)));
+        assert_eq!(source.original_line(4), Some((root.as_path(), 3)));
+        assert_eq!(source.files().len(), 2);
+    }
+
+    #[test]
+    fn defines_follow_version() {
+        let dir = temp_dir("define");
+        let root = write(&dir, "main.glslf", "#version 330 core\nvoid main() {}\n");
+        let defines = vec![("USE_FOG".to_string(), "1".to_string())];
+
+        let source = preprocess(&root, &defines).unwrap();
+
+        assert_eq!(source.text, "#version 330 core\n#define USE_FOG 1\nvoid main() {}\n");
+        assert_eq!(source.original_line(3), Some((root.as_path(), 2)));
+    }
+
+    #[test]
+    fn text_sources_include_files_next_to_their_name() {
+        let dir = temp_dir("text");
+        write(&dir, "common.glsl", "uniform mat4 model;\n");
+        let name = dir.join("inline.glslv");
+
+        let source = preprocess_str(&name, "#version 330 core\n#include \"common.glsl\"\nvoid main() {}\n", &[]).unwrap();
+
+        assert_eq!(source.text, "#version 330 core\nuniform mat4 model;\nvoid main() {}\n");
+        assert_eq!(source.original_line(3), Some((name.as_path(), 3)));
+        assert_eq!(source.files(), &[name.clone(), dir.join("common.glsl")]);
+    }
+
+    #[test]
+    fn include_cycles_are_reported() {
+        let dir = temp_dir("cycle");
+        write(&dir, "a.glsl", "#include \"b.glsl\"\n");
+        write(&dir, "b.glsl", "#include \"a.glsl\"\n");
+
+        match preprocess(&dir.join("a.glsl"), &[]) {
+            Err(ShaderError::IncludeCycle { chain }) => assert_eq!(chain.len(), 3),
+            other => panic!("expected an include cycle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn malformed_include_is_an_error() {
+        let dir = temp_dir("malformed");
+        let root = write(&dir, "main.glslv", "#version 330 core\n#include common.glsl\n");
+
+        match preprocess(&root, &[]) {
+            Err(ShaderError::Preprocess { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a preprocess error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use std::ffi::CString;
+use std::fmt;
+use std::ops::Range;
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+use mesh::VertexLayout;
+
+use super::Shader;
+
+/// An active uniform of a linked program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UniformInfo {
+    /// As declared: GL's `[0]` suffix of arrays is removed.
+    pub name: String,
+    pub ty: GLenum,
+    pub array_size: GLint,
+    /// `None` for uniforms that live in a uniform block.
+    pub location: Option<GLint>,
+    pub block_index: Option<GLuint>,
+}
+
+impl UniformInfo {
+    pub fn is_sampler(&self) -> bool {
+        is_sampler(self.ty)
+    }
+}
+
+/// An active uniform block of a linked program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UniformBlockInfo {
+    pub name: String,
+    pub index: GLuint,
+    pub binding: GLuint,
+    pub data_size: GLint,
+    pub active_uniforms: GLint,
+}
+
+/// An active vertex input (`layout (location = N) in ...`) of a linked program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeInfo {
+    pub name: String,
+    pub ty: GLenum,
+    pub array_size: GLint,
+    pub location: GLint,
+}
+
+impl AttributeInfo {
+    /// Number of scalar components per location (e.g. 3 for `vec3`, 4 for
+    /// each column of a `mat4`).
+    pub fn components(&self) -> Option<GLint> {
+        location_shape(self.ty).map(|(_, components)| components)
+    }
+
+    /// The locations the attribute takes up: one per matrix column and array
+    /// element.
+    pub fn locations(&self) -> Range<GLint> {
+        let per_element = location_shape(self.ty).map_or(1, |(locations, _)| locations);
+        self.location..self.location + per_element * self.array_size.max(1)
+    }
+}
+
+/// An attribute location a vertex layout leaves empty or feeds with a
+/// different number of components than the shader reads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutMismatch {
+    pub attribute: String,
+    pub location: GLint,
+    pub expected: GLint,
+    /// `None` when the layout has nothing at `location`.
+    pub provided: Option<GLint>,
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "attribute `{}` reads {} components at location {}, ", self.attribute, self.expected, self.location)?;
+        match self.provided {
+            Some(provided) => write!(f, "the vertex layout provides {}", provided),
+            None => write!(f, "the vertex layout provides none"),
+        }
+    }
+}
+
+/// Everything a linked program expects from the outside.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProgramReflection {
+    pub uniforms: Vec<UniformInfo>,
+    pub uniform_blocks: Vec<UniformBlockInfo>,
+    pub attributes: Vec<AttributeInfo>,
+}
+
+impl ProgramReflection {
+    /// Queries the active uniforms, uniform blocks and attributes of `program`.
+    pub fn from_program(program: GLuint) -> ProgramReflection {
+        unsafe {
+            ProgramReflection {
+                uniforms: Self::query_uniforms(program),
+                uniform_blocks: Self::query_uniform_blocks(program),
+                attributes: Self::query_attributes(program),
+            }
+        }
+    }
+
+    pub fn uniform(&self, name: &str) -> Option<&UniformInfo> {
+        self.uniforms.iter().find(|uniform| uniform.name == name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&AttributeInfo> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
+    }
+
+    pub fn samplers(&self) -> Vec<&UniformInfo> {
+        self.uniforms.iter().filter(|uniform| uniform.is_sampler()).collect()
+    }
+
+    /// Attributes the program reads with a location that is not in
+    /// `locations`, i.e. inputs a vertex layout providing exactly `locations`
+    /// would leave undefined. Matrices need all of their column locations.
+    pub fn missing_attributes(&self, locations: &[GLuint]) -> Vec<&AttributeInfo> {
+        self.attributes.iter()
+            .filter(|attribute| !attribute.locations().all(|location| locations.contains(&(location as GLuint))))
+            .collect()
+    }
+
+    /// Every attribute location `layout` leaves empty or feeds with a
+    /// different number of components than the program reads, e.g. a `vec2`
+    /// vertex field bound to a `vec3` input. Empty when the mesh fits.
+    pub fn check_layout(&self, layout: &VertexLayout) -> Vec<LayoutMismatch> {
+        let mut mismatches = Vec::new();
+        for attribute in &self.attributes {
+            let expected = match attribute.components() {
+                Some(components) => components,
+                None => continue,
+            };
+            for location in attribute.locations() {
+                let provided = layout.attributes.iter()
+                    .find(|provided| provided.location as GLint == location)
+                    .map(|provided| provided.components);
+                if provided != Some(expected) {
+                    mismatches.push(LayoutMismatch { attribute: attribute.name.clone(), location, expected, provided });
+                }
+            }
+        }
+        mismatches
+    }
+
+    unsafe fn query_uniforms(program: GLuint) -> Vec<UniformInfo> {
+        let count = program_iv(program, gl::ACTIVE_UNIFORMS);
+        let max_len = program_iv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH);
+
+        (0..count as GLuint).map(|index| {
+            let (name, array_size, ty) = active_resource(max_len, |buf_len, len, size, ty, name| {
+                gl::GetActiveUniform(program, index, buf_len, len, size, ty, name)
+            });
+
+            let mut block_index = -1;
+            gl::GetActiveUniformsiv(program, 1, &index, gl::UNIFORM_BLOCK_INDEX, &mut block_index);
+
+            let location = uniform_location(program, &name);
+
+            UniformInfo {
+                name: declared_name(name),
+                ty,
+                array_size,
+                location: if location == -1 { None } else { Some(location) },
+                block_index: if block_index == -1 { None } else { Some(block_index as GLuint) },
+            }
+        }).collect()
+    }
+
+    unsafe fn query_uniform_blocks(program: GLuint) -> Vec<UniformBlockInfo> {
+        let count = program_iv(program, gl::ACTIVE_UNIFORM_BLOCKS);
+        let max_len = program_iv(program, gl::ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH);
+
+        (0..count as GLuint).map(|index| {
+            let mut buf = vec![0u8; max_len.max(1) as usize];
+            let mut len = 0;
+            gl::GetActiveUniformBlockName(program, index, buf.len() as GLsizei, &mut len, buf.as_mut_ptr() as *mut GLchar);
+            buf.truncate(len as usize);
+
+            let block_iv = |pname| {
+                let mut value = 0;
+                gl::GetActiveUniformBlockiv(program, index, pname, &mut value);
+                value
+            };
+
+            UniformBlockInfo {
+                name: String::from_utf8_lossy(&buf).into_owned(),
+                index,
+                binding: block_iv(gl::UNIFORM_BLOCK_BINDING) as GLuint,
+                data_size: block_iv(gl::UNIFORM_BLOCK_DATA_SIZE),
+                active_uniforms: block_iv(gl::UNIFORM_BLOCK_ACTIVE_UNIFORMS),
+            }
+        }).collect()
+    }
+
+    unsafe fn query_attributes(program: GLuint) -> Vec<AttributeInfo> {
+        let count = program_iv(program, gl::ACTIVE_ATTRIBUTES);
+        let max_len = program_iv(program, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH);
+
+        let mut attributes: Vec<AttributeInfo> = (0..count as GLuint).map(|index| {
+            let (name, array_size, ty) = active_resource(max_len, |buf_len, len, size, ty, name| {
+                gl::GetActiveAttrib(program, index, buf_len, len, size, ty, name)
+            });
+
+            let c_name = CString::new(name.as_bytes()).unwrap();
+            let location = gl::GetAttribLocation(program, c_name.as_ptr());
+
+            AttributeInfo { name: declared_name(name), ty, array_size, location }
+        })
+        // Built-ins such as gl_VertexID are reported as active but have no location
+        .filter(|attribute| attribute.location != -1)
+        .collect();
+
+        attributes.sort_by_key(|attribute| attribute.location);
+        attributes
+    }
+}
+
+impl Shader {
+    /// Enumerates what the linked program expects: uniforms, uniform blocks,
+    /// samplers and vertex attributes.
+    pub fn reflect(&self) -> ProgramReflection {
+        ProgramReflection::from_program(self.program)
+    }
+}
+
+/// Returns true for every `sampler*` GLSL type.
+pub fn is_sampler(ty: GLenum) -> bool {
+    matches!(ty,
+        gl::SAMPLER_1D | gl::SAMPLER_2D | gl::SAMPLER_3D | gl::SAMPLER_CUBE |
+        gl::SAMPLER_1D_SHADOW | gl::SAMPLER_2D_SHADOW | gl::SAMPLER_CUBE_SHADOW |
+        gl::SAMPLER_1D_ARRAY | gl::SAMPLER_2D_ARRAY |
+        gl::SAMPLER_1D_ARRAY_SHADOW | gl::SAMPLER_2D_ARRAY_SHADOW |
+        gl::SAMPLER_2D_MULTISAMPLE | gl::SAMPLER_2D_MULTISAMPLE_ARRAY |
+        gl::SAMPLER_CUBE_MAP_ARRAY | gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW |
+        gl::SAMPLER_BUFFER | gl::SAMPLER_2D_RECT | gl::SAMPLER_2D_RECT_SHADOW |
+        gl::INT_SAMPLER_1D | gl::INT_SAMPLER_2D | gl::INT_SAMPLER_3D | gl::INT_SAMPLER_CUBE |
+        gl::INT_SAMPLER_1D_ARRAY | gl::INT_SAMPLER_2D_ARRAY |
+        gl::INT_SAMPLER_2D_MULTISAMPLE | gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY |
+        gl::INT_SAMPLER_CUBE_MAP_ARRAY | gl::INT_SAMPLER_BUFFER | gl::INT_SAMPLER_2D_RECT |
+        gl::UNSIGNED_INT_SAMPLER_1D | gl::UNSIGNED_INT_SAMPLER_2D | gl::UNSIGNED_INT_SAMPLER_3D |
+        gl::UNSIGNED_INT_SAMPLER_CUBE | gl::UNSIGNED_INT_SAMPLER_1D_ARRAY |
+        gl::UNSIGNED_INT_SAMPLER_2D_ARRAY | gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE |
+        gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY | gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY |
+        gl::UNSIGNED_INT_SAMPLER_BUFFER | gl::UNSIGNED_INT_SAMPLER_2D_RECT)
+}
+
+/// Number of scalar components of a GLSL scalar, vector or matrix type as
+/// reported by `glGetActiveAttrib`/`glGetActiveUniform`.
+pub fn component_count(ty: GLenum) -> Option<GLint> {
+    let count = match ty {
+        gl::FLOAT | gl::INT | gl::UNSIGNED_INT | gl::DOUBLE | gl::BOOL => 1,
+        gl::FLOAT_VEC2 | gl::INT_VEC2 | gl::UNSIGNED_INT_VEC2 | gl::DOUBLE_VEC2 | gl::BOOL_VEC2 => 2,
+        gl::FLOAT_VEC3 | gl::INT_VEC3 | gl::UNSIGNED_INT_VEC3 | gl::DOUBLE_VEC3 | gl::BOOL_VEC3 => 3,
+        gl::FLOAT_VEC4 | gl::INT_VEC4 | gl::UNSIGNED_INT_VEC4 | gl::DOUBLE_VEC4 | gl::BOOL_VEC4 => 4,
+        gl::FLOAT_MAT2 | gl::DOUBLE_MAT2 => 4,
+        gl::FLOAT_MAT3 | gl::DOUBLE_MAT3 => 9,
+        gl::FLOAT_MAT4 | gl::DOUBLE_MAT4 => 16,
+        gl::FLOAT_MAT2x3 | gl::FLOAT_MAT3x2 | gl::DOUBLE_MAT2x3 | gl::DOUBLE_MAT3x2 => 6,
+        gl::FLOAT_MAT2x4 | gl::FLOAT_MAT4x2 | gl::DOUBLE_MAT2x4 | gl::DOUBLE_MAT4x2 => 8,
+        gl::FLOAT_MAT3x4 | gl::FLOAT_MAT4x3 | gl::DOUBLE_MAT3x4 | gl::DOUBLE_MAT4x3 => 12,
+        _ => return None,
+    };
+    Some(count)
+}
+
+/// Splits a matrix type into its columns, each taking one attribute location:
+/// `(locations, components per location)`. Other types take one location.
+fn location_shape(ty: GLenum) -> Option<(GLint, GLint)> {
+    let shape = match ty {
+        gl::FLOAT_MAT2 | gl::DOUBLE_MAT2 => (2, 2),
+        gl::FLOAT_MAT3 | gl::DOUBLE_MAT3 => (3, 3),
+        gl::FLOAT_MAT4 | gl::DOUBLE_MAT4 => (4, 4),
+        gl::FLOAT_MAT2x3 | gl::DOUBLE_MAT2x3 => (2, 3),
+        gl::FLOAT_MAT2x4 | gl::DOUBLE_MAT2x4 => (2, 4),
+        gl::FLOAT_MAT3x2 | gl::DOUBLE_MAT3x2 => (3, 2),
+        gl::FLOAT_MAT3x4 | gl::DOUBLE_MAT3x4 => (3, 4),
+        gl::FLOAT_MAT4x2 | gl::DOUBLE_MAT4x2 => (4, 2),
+        gl::FLOAT_MAT4x3 | gl::DOUBLE_MAT4x3 => (4, 3),
+        _ => (1, component_count(ty)?),
+    };
+    Some(shape)
+}
+
+/// GL reports arrays as `name[0]`; lookups use the declared name.
+fn declared_name(mut name: String) -> String {
+    if name.ends_with("[0]") {
+        name.truncate(name.len() - 3);
+    }
+    name
+}
+
+unsafe fn program_iv(program: GLuint, pname: GLenum) -> GLint {
+    let mut value = 0;
+    gl::GetProgramiv(program, pname, &mut value);
+    value
+}
+
+unsafe fn uniform_location(program: GLuint, name: &str) -> GLint {
+    let c_name = CString::new(name.as_bytes()).unwrap();
+    gl::GetUniformLocation(program, c_name.as_ptr())
+}
+
+/// Shared body of `glGetActiveUniform`/`glGetActiveAttrib`, which have the same
+/// signature. Returns `(name, array size, type)`.
+unsafe fn active_resource<F>(max_len: GLint, query: F) -> (String, GLint, GLenum)
+    where F: FnOnce(GLsizei, *mut GLsizei, *mut GLint, *mut GLenum, *mut GLchar)
+{
+    let mut buf = vec![0u8; max_len.max(1) as usize];
+    let mut len = 0;
+    let mut size = 0;
+    let mut ty = 0;
+    query(buf.len() as GLsizei, &mut len, &mut size, &mut ty, buf.as_mut_ptr() as *mut GLchar);
+    buf.truncate(len as usize);
+
+    (String::from_utf8_lossy(&buf).into_owned(), size, ty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mesh::VertexAttribute;
+
+    fn attribute(name: &str, ty: GLenum, location: GLint) -> AttributeInfo {
+        AttributeInfo { name: name.to_string(), ty, array_size: 1, location }
+    }
+
+    fn provided(location: GLuint, components: GLint) -> VertexAttribute {
+        VertexAttribute { location, components, ty: gl::FLOAT, normalized: false, offset: 0 }
+    }
+
+    #[test]
+    fn sampler_types() {
+        assert!(is_sampler(gl::SAMPLER_2D));
+        assert!(is_sampler(gl::INT_SAMPLER_CUBE));
+        assert!(is_sampler(gl::UNSIGNED_INT_SAMPLER_2D_ARRAY));
+        assert!(!is_sampler(gl::FLOAT_VEC4));
+    }
+
+    #[test]
+    fn component_counts() {
+        assert_eq!(component_count(gl::FLOAT), Some(1));
+        assert_eq!(component_count(gl::INT_VEC3), Some(3));
+        assert_eq!(component_count(gl::FLOAT_MAT4), Some(16));
+        assert_eq!(component_count(gl::FLOAT_MAT2x3), Some(6));
+        assert_eq!(component_count(gl::SAMPLER_2D), None);
+    }
+
+    #[test]
+    fn matrices_take_a_location_per_column() {
+        let matrix = attribute("instance", gl::FLOAT_MAT4x3, 2);
+
+        assert_eq!(matrix.locations(), 2..6);
+        assert_eq!(matrix.components(), Some(3));
+    }
+
+    #[test]
+    fn missing_attributes_are_filtered_by_location() {
+        let reflection = ProgramReflection {
+            attributes: vec![
+                attribute("position", gl::FLOAT_VEC3, 0),
+                attribute("uv", gl::FLOAT_VEC2, 1),
+                attribute("instance", gl::FLOAT_MAT4, 2),
+            ],
+            ..ProgramReflection::default()
+        };
+
+        let names = |missing: Vec<&AttributeInfo>| missing.iter().map(|attribute| attribute.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(reflection.missing_attributes(&[0, 1, 2, 3, 4, 5])), Vec::<String>::new());
+        assert_eq!(names(reflection.missing_attributes(&[0, 2])), vec!["uv", "instance"]);
+    }
+
+    #[test]
+    fn layouts_are_checked_per_location() {
+        let reflection = ProgramReflection {
+            attributes: vec![attribute("position", gl::FLOAT_VEC3, 0), attribute("instance", gl::FLOAT_MAT2, 1)],
+            ..ProgramReflection::default()
+        };
+
+        let fitting = VertexLayout::new(28).attribute(provided(0, 3)).attribute(provided(1, 2)).attribute(provided(2, 2));
+        assert!(reflection.check_layout(&fitting).is_empty());
+
+        let short = VertexLayout::new(16).attribute(provided(0, 2)).attribute(provided(1, 2));
+        let mismatch = |location, expected, provided| LayoutMismatch {
+            attribute: if location == 0 { "position" } else { "instance" }.to_string(),
+            location,
+            expected,
+            provided,
+        };
+        assert_eq!(reflection.check_layout(&short), vec![mismatch(0, 3, Some(2)), mismatch(2, 2, None)]);
+    }
+
+    #[test]
+    fn array_names_lose_their_index() {
+        assert_eq!(declared_name("x[0]".to_string()), "x");
+        assert_eq!(declared_name("lights[0].color".to_string()), "lights[0].color");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn active_resources_are_reflected() {
+        use headless::HeadlessContext;
+        use shader::{ShaderBuilder, ShaderStage};
+
+        // Fails rather than passing untested where there is no EGL
+        let _context = HeadlessContext::new(1, 1)
+            .unwrap_or_else(|err| panic!("no headless GL context ({}); this test needs Mesa's EGL", err));
+
+        let shader = ShaderBuilder::new()
+            .stage_source(ShaderStage::Vertex, "reflect.glslv", "#version 330 core
+layout (location = 2) in vec3 position;
+layout (std140) uniform Matrices { mat4 projection; mat4 view; };
+uniform float x[4];
+void main() { gl_Position = projection * view * vec4(position * (x[0] + x[3]), 1.0); }
+")
+            .stage_source(ShaderStage::Fragment, "reflect.glslf", "#version 330 core
+uniform sampler2D diffuse;
+out vec4 color;
+void main() { color = texture(diffuse, gl_FragCoord.xy); }
+")
+            .build()
+            .unwrap();
+        let reflection = shader.reflect();
+
+        assert_eq!(reflection.attributes, vec![AttributeInfo { name: "position".to_string(), ty: gl::FLOAT_VEC3, array_size: 1, location: 2 }]);
+
+        let x = reflection.uniform("x").unwrap();
+        assert_eq!((x.ty, x.array_size, x.block_index), (gl::FLOAT, 4, None));
+        assert!(x.location.is_some());
+
+        let samplers = reflection.samplers();
+        assert_eq!(samplers.len(), 1);
+        assert_eq!((samplers[0].name.as_str(), samplers[0].ty), ("diffuse", gl::SAMPLER_2D));
+
+        assert_eq!(reflection.uniform_blocks.len(), 1);
+        let block = &reflection.uniform_blocks[0];
+        assert_eq!((block.name.as_str(), block.data_size, block.active_uniforms), ("Matrices", 128, 2));
+        let view = reflection.uniform("view").unwrap();
+        assert_eq!((view.ty, view.location, view.block_index), (gl::FLOAT_MAT4, None, Some(block.index)));
+    }
+}
@@ -0,0 +1,109 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{Shader, ShaderBuilder, ShaderError};
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A `Shader` that recompiles itself when its source files change on disk.
+///
+/// Changes are detected by polling the modification times of the stage sources
+/// and everything they `#include`. If the new sources fail to compile or link,
+/// the previous program stays in use.
+#[derive(Debug)]
+pub struct ReloadableShader {
+    shader: Shader,
+    builder: ShaderBuilder,
+    /// Files of the last build, so polling doesn't preprocess every source.
+    dependencies: Vec<PathBuf>,
+    modified: Vec<(PathBuf, Option<SystemTime>)>,
+    poll_interval: Duration,
+    last_poll: Instant,
+}
+
+impl ReloadableShader {
+    pub fn from_source<P: AsRef<Path>>(vertex: P, fragment: P) -> Result<ReloadableShader, ShaderError> {
+        Self::from_builder(ShaderBuilder::new().vertex(vertex).fragment(fragment))
+    }
+
+    /// Watches every stage source of `builder`.
+    pub fn from_builder(builder: ShaderBuilder) -> Result<ReloadableShader, ShaderError> {
+        let shader = builder.build()?;
+        let dependencies = builder.dependencies();
+
+        let mut reloadable = ReloadableShader {
+            shader,
+            builder,
+            dependencies,
+            modified: Vec::new(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            last_poll: Instant::now(),
+        };
+        reloadable.modified = reloadable.modification_times();
+
+        Ok(reloadable)
+    }
+
+    /// Sets the minimum time between two checks of the source files.
+    pub fn set_poll_interval(&mut self, interval: Duration) {
+        self.poll_interval = interval;
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+
+    pub fn gl_use(&self) {
+        self.shader.gl_use();
+    }
+
+    /// Recompiles the program if any source file changed since the last check.
+    ///
+    /// Returns `Ok(true)` when a new program was swapped in. On error the old
+    /// program is kept and the error is only reported once per change.
+    pub fn reload_if_changed(&mut self) -> Result<bool, ShaderError> {
+        if self.last_poll.elapsed() < self.poll_interval {
+            return Ok(false);
+        }
+        self.last_poll = Instant::now();
+
+        let modified = self.modification_times();
+        if modified == self.modified {
+            return Ok(false);
+        }
+        self.modified = modified;
+
+        let result = self.reload().map(|_| true);
+        // Includes may have been added or removed
+        self.modified = self.modification_times();
+        result
+    }
+
+    /// Unconditionally recompiles the program from disk.
+    pub fn reload(&mut self) -> Result<(), ShaderError> {
+        let dependencies = self.builder.dependencies();
+        match self.builder.build() {
+            Ok(shader) => {
+                // The replaced program is deleted when it is dropped
+                self.shader = shader;
+                self.dependencies = dependencies;
+                Ok(())
+            },
+            Err(err) => {
+                // Keep watching the old files too, so fixing an include of
+                // the last working version is still picked up
+                self.dependencies.extend(dependencies);
+                self.dependencies.sort();
+                self.dependencies.dedup();
+                Err(err)
+            },
+        }
+    }
+
+    fn modification_times(&self) -> Vec<(PathBuf, Option<SystemTime>)> {
+        self.dependencies.iter()
+            .map(|path| (path.clone(), fs::metadata(path).and_then(|meta| meta.modified()).ok()))
+            .collect()
+    }
+}
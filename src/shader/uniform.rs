@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CString;
+
+use cgmath::{Matrix, Matrix3, Matrix4, Vector2, Vector3, Vector4};
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+use super::Shader;
+
+/// Lazily filled map from program and uniform name to location.
+///
+/// Unknown names are cached as `-1` so the lookup and the warning happen once.
+/// Locations are kept per program, so a cache that sees a relinked program,
+/// e.g. after a reload, never hands out the old program's locations.
+#[derive(Debug, Default)]
+pub struct UniformCache {
+    locations: RefCell<HashMap<(GLuint, String), GLint>>,
+}
+
+impl UniformCache {
+    pub fn new() -> UniformCache {
+        UniformCache::default()
+    }
+
+    pub fn location(&self, program: GLuint, name: &str) -> GLint {
+        self.location_with(program, name, || match CString::new(name) {
+            Ok(c_name) => unsafe { gl::GetUniformLocation(program, c_name.as_ptr()) },
+            Err(_) => -1,
+        })
+    }
+
+    fn location_with<F: FnOnce() -> GLint>(&self, program: GLuint, name: &str, lookup: F) -> GLint {
+        let key = (program, name.to_string());
+        if let Some(&location) = self.locations.borrow().get(&key) {
+            return location;
+        }
+
+        let location = lookup();
+        if location == -1 {
+            eprintln!("warning: uniform `{}` is not an active uniform of program {}", name, program);
+        }
+
+        self.locations.borrow_mut().insert(key, location);
+        location
+    }
+
+    pub fn clear(&self) {
+        self.locations.borrow_mut().clear();
+    }
+}
+
+/// Typed uniform setters. The program has to be in use (`gl_use`) when they
+/// are called; names that are not active uniforms are ignored with a warning.
+impl Shader {
+    pub fn uniform_location(&self, name: &str) -> GLint {
+        self.uniforms.location(self.program, name)
+    }
+
+    pub fn set_bool(&self, name: &str, value: bool) {
+        self.set_i32(name, value as i32);
+    }
+
+    pub fn set_i32(&self, name: &str, value: i32) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::Uniform1i(location, value); }
+        }
+    }
+
+    pub fn set_u32(&self, name: &str, value: u32) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::Uniform1ui(location, value); }
+        }
+    }
+
+    pub fn set_f32(&self, name: &str, value: f32) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::Uniform1f(location, value); }
+        }
+    }
+
+    /// Binds a sampler uniform to texture unit `unit` (`gl::TEXTURE0 + unit`).
+    pub fn set_sampler(&self, name: &str, unit: u32) {
+        self.set_i32(name, unit as i32);
+    }
+
+    pub fn set_vec2(&self, name: &str, value: Vector2<f32>) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::Uniform2f(location, value.x, value.y); }
+        }
+    }
+
+    pub fn set_vec3(&self, name: &str, value: Vector3<f32>) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::Uniform3f(location, value.x, value.y, value.z); }
+        }
+    }
+
+    pub fn set_vec4(&self, name: &str, value: Vector4<f32>) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::Uniform4f(location, value.x, value.y, value.z, value.w); }
+        }
+    }
+
+    pub fn set_mat3(&self, name: &str, value: &Matrix3<f32>) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::UniformMatrix3fv(location, 1, gl::FALSE, value.as_ptr()); }
+        }
+    }
+
+    pub fn set_mat4(&self, name: &str, value: &Matrix4<f32>) {
+        if let Some(location) = self.active_location(name) {
+            unsafe { gl::UniformMatrix4fv(location, 1, gl::FALSE, value.as_ptr()); }
+        }
+    }
+
+    fn active_location(&self, name: &str) -> Option<GLint> {
+        match self.uniform_location(name) {
+            -1 => None,
+            location => Some(location),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locations_are_cached_per_program() {
+        let cache = UniformCache::new();
+
+        assert_eq!(cache.location_with(1, "model", || 3), 3);
+        // Cached: the lookup is not called again
+        assert_eq!(cache.location_with(1, "model", || panic!("looked up twice")), 3);
+        // A relinked program has its own locations
+        assert_eq!(cache.location_with(2, "model", || 5), 5);
+        assert_eq!(cache.location_with(1, "model", || panic!("looked up twice")), 3);
+
+        cache.clear();
+        assert_eq!(cache.location_with(1, "model", || 4), 4);
+    }
+}
@@ -0,0 +1,116 @@
+use cgmath::{Matrix4, Vector4};
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+use mesh::Mesh;
+use shader::{Shader, ShaderBuilder, ShaderError, ShaderStage};
+use texture::CubeMap;
+
+// Embedded, so the skybox works wherever the binary runs from
+const VERTEX_SHADER: &str = include_str!("shader/skybox.glslv");
+const FRAGMENT_SHADER: &str = include_str!("shader/skybox.glslf");
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+struct SkyboxVertex {
+    position: [f32; 3],
+}
+
+impl_vertex!(SkyboxVertex { position: 0 });
+
+/// A cube map drawn around the viewer, behind everything else.
+#[derive(Debug)]
+pub struct Skybox {
+    cube_map: CubeMap,
+    mesh: Mesh,
+    shader: Shader,
+}
+
+impl Skybox {
+    /// Needs a current OpenGL context.
+    pub fn new(cube_map: CubeMap) -> Result<Skybox, ShaderError> {
+        let shader = ShaderBuilder::new()
+            .stage_source(ShaderStage::Vertex, "skybox.glslv", VERTEX_SHADER)
+            .stage_source(ShaderStage::Fragment, "skybox.glslf", FRAGMENT_SHADER)
+            .build()?;
+
+        Ok(Skybox::with_shader(cube_map, shader))
+    }
+
+    /// Like `new`, but draws with `shader`, e.g. one built from src/skybox/shader.
+    pub fn with_shader(cube_map: CubeMap, shader: Shader) -> Skybox {
+        // Corner i has x, y and z set from its bits 0, 1 and 2
+        let corners: Vec<SkyboxVertex> = (0..8)
+            .map(|i| {
+                let coordinate = |bit| if i & bit == 0 { -1.0 } else { 1.0 };
+                SkyboxVertex { position: [coordinate(1), coordinate(2), coordinate(4)] }
+            })
+            .collect();
+        // Wound counter-clockwise as seen from inside the cube
+        let indices = [
+            1, 7, 3, 1, 5, 7, // +X
+            0, 2, 6, 0, 6, 4, // -X
+            2, 3, 7, 2, 7, 6, // +Y
+            0, 4, 5, 0, 5, 1, // -Y
+            4, 6, 7, 4, 7, 5, // +Z
+            0, 3, 2, 0, 1, 3, // -Z
+        ];
+
+        Skybox {
+            cube_map,
+            mesh: Mesh::with_indices(&corners, &indices),
+            shader,
+        }
+    }
+
+    pub fn cube_map(&self) -> &CubeMap {
+        &self.cube_map
+    }
+
+    /// Draws the sky with `view` (e.g. `Camera::get_view`) stripped of its
+    /// translation, so the sky stays put as the camera moves.
+    ///
+    /// Call it after the scene with depth testing enabled: the sky is drawn at
+    /// the far plane with `GL_LEQUAL`, so it only fills the pixels the scene
+    /// left empty. Uses texture unit 0.
+    pub fn draw(&self, view: &Matrix4<f32>, projection: &Matrix4<f32>) {
+        self.shader.gl_use();
+        self.shader.set_mat4("view", &without_translation(view));
+        self.shader.set_mat4("projection", projection);
+
+        self.cube_map.bind_to_unit(0);
+        self.shader.set_sampler("skybox", 0);
+
+        unsafe {
+            let mut depth_func = 0;
+            gl::GetIntegerv(gl::DEPTH_FUNC, &mut depth_func);
+            gl::DepthFunc(gl::LEQUAL);
+
+            self.mesh.draw();
+
+            gl::DepthFunc(depth_func as GLenum);
+        }
+    }
+}
+
+/// `view` with only its rotation (and scale) left.
+pub fn without_translation(view: &Matrix4<f32>) -> Matrix4<f32> {
+    let mut rotation = *view;
+    rotation.w = Vector4::unit_w();
+    rotation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::{Deg, Vector3};
+
+    #[test]
+    fn translation_is_removed() {
+        let rotation = Matrix4::from_angle_y(Deg(30.0));
+        let view = rotation * Matrix4::from_translation(Vector3::new(1.0, 2.0, 3.0));
+
+        assert_eq!(without_translation(&view), rotation);
+    }
+}
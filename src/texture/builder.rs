@@ -0,0 +1,299 @@
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::path::Path;
+
+use image::{self, DynamicImage};
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+use object::Texture;
+
+use super::cube_map::{cross_faces, equirectangular_faces, CubeFace, CubeMap};
+use super::format::{pixel_data, unpack_alignment, PixelFormat};
+use super::{dimensions, ColorSpace, Filter, MinFilter, Texture2D, TextureError, Wrap};
+
+// GL_EXT_texture_filter_anisotropic, core since 4.6
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+/// Sampling and upload options for a `Texture2D`.
+///
+/// ```no_run
+/// # use opengl_sdl2_demo::texture::{ColorSpace, MinFilter, Texture2D, Wrap};
+/// let texture = Texture2D::builder()
+///     .wrap(Wrap::ClampToEdge)
+///     .min_filter(MinFilter::LinearMipmapLinear)
+///     .anisotropy(8.0)
+///     .color_space(ColorSpace::Srgb)
+///     .flip_vertically(true)
+///     .open("resources/container.jpg")
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextureBuilder {
+    wrap_s: Wrap,
+    wrap_t: Wrap,
+    min_filter: MinFilter,
+    mag_filter: Filter,
+    mipmaps: bool,
+    anisotropy: f32,
+    color_space: ColorSpace,
+    flip_vertically: bool,
+}
+
+impl Default for TextureBuilder {
+    fn default() -> TextureBuilder {
+        TextureBuilder::new()
+    }
+}
+
+impl TextureBuilder {
+    /// Repeating, trilinear filtered and mipmapped linear data, uploaded as
+    /// stored (first row at `v = 0`).
+    pub fn new() -> TextureBuilder {
+        TextureBuilder {
+            wrap_s: Wrap::Repeat,
+            wrap_t: Wrap::Repeat,
+            min_filter: MinFilter::LinearMipmapLinear,
+            mag_filter: Filter::Linear,
+            mipmaps: true,
+            anisotropy: 1.0,
+            color_space: ColorSpace::Linear,
+            flip_vertically: false,
+        }
+    }
+
+    /// Sets the wrap mode of both axes.
+    pub fn wrap(self, wrap: Wrap) -> TextureBuilder {
+        self.wrap_s(wrap).wrap_t(wrap)
+    }
+
+    pub fn wrap_s(mut self, wrap: Wrap) -> TextureBuilder {
+        self.wrap_s = wrap;
+        self
+    }
+
+    pub fn wrap_t(mut self, wrap: Wrap) -> TextureBuilder {
+        self.wrap_t = wrap;
+        self
+    }
+
+    pub fn min_filter(mut self, filter: MinFilter) -> TextureBuilder {
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn mag_filter(mut self, filter: Filter) -> TextureBuilder {
+        self.mag_filter = filter;
+        self
+    }
+
+    /// Whether to generate mipmaps. Without them a mipmapped min filter falls
+    /// back to its base level equivalent.
+    pub fn mipmaps(mut self, mipmaps: bool) -> TextureBuilder {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    /// Maximum anisotropic filtering samples, clamped to what the driver
+    /// supports. `1.0` (the default) disables it; it is ignored where the
+    /// extension is missing.
+    pub fn anisotropy(mut self, samples: f32) -> TextureBuilder {
+        self.anisotropy = samples.max(1.0);
+        self
+    }
+
+    /// `Srgb` for color maps authored in sRGB, so sampling returns linear
+    /// values. Data maps (normals, roughness...) stay `Linear`.
+    pub fn color_space(mut self, color_space: ColorSpace) -> TextureBuilder {
+        self.color_space = color_space;
+        self
+    }
+
+    /// Flips the image so its last row is at `v = 0`, the convention of most
+    /// modelling tools.
+    pub fn flip_vertically(mut self, flip: bool) -> TextureBuilder {
+        self.flip_vertically = flip;
+        self
+    }
+
+    /// Loads and uploads the image at `path`.
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<Texture2D, TextureError> {
+        self.build(&open_image(path.as_ref())?)
+    }
+
+    /// Uploads `image` to a new texture. Needs a current OpenGL context.
+    pub fn build(&self, image: &DynamicImage) -> Result<Texture2D, TextureError> {
+        let flipped;
+        let image = if self.flip_vertically {
+            flipped = image.flipv();
+            &flipped
+        } else {
+            image
+        };
+
+        let (width, height) = dimensions(image);
+        check_size(width, height, gl::MAX_TEXTURE_SIZE)?;
+
+        let texture = Texture::new(gl::TEXTURE_2D);
+        texture.bind();
+        let format = upload(gl::TEXTURE_2D, image, self.color_space);
+        self.apply_sampling(gl::TEXTURE_2D, format);
+        texture.unbind();
+
+        Ok(Texture2D {
+            texture,
+            width,
+            height,
+            format,
+        })
+    }
+
+    /// Loads six face images, in `CubeFace::ALL` order, into a cube map.
+    pub fn open_cube_map<P: AsRef<Path>>(&self, paths: &[P; 6]) -> Result<CubeMap, TextureError> {
+        let mut faces = Vec::with_capacity(6);
+        for path in paths {
+            faces.push(open_image(path.as_ref())?);
+        }
+        let mut faces = faces.into_iter();
+        let mut next = || faces.next().unwrap();
+        self.build_cube_map(&[next(), next(), next(), next(), next(), next()])
+    }
+
+    /// Loads a cube map laid out as a cross; see `cross_faces`.
+    pub fn open_cross<P: AsRef<Path>>(&self, path: P) -> Result<CubeMap, TextureError> {
+        self.build_cube_map(&cross_faces(&open_image(path.as_ref())?)?)
+    }
+
+    /// Loads an equirectangular panorama into a cube map with faces a
+    /// quarter of its width, which keeps the resolution at the horizon.
+    pub fn open_equirectangular<P: AsRef<Path>>(&self, path: P) -> Result<CubeMap, TextureError> {
+        let image = open_image(path.as_ref())?;
+        let size = (dimensions(&image).0 / 4).max(1);
+        self.build_cube_map(&equirectangular_faces(&image, size))
+    }
+
+    /// Uploads six square faces of the same size, in `CubeFace::ALL` order.
+    /// Needs a current OpenGL context.
+    ///
+    /// Also enables `GL_TEXTURE_CUBE_MAP_SEAMLESS`, so filtering crosses face
+    /// edges; the wrap modes then have no effect.
+    pub fn build_cube_map(&self, faces: &[DynamicImage; 6]) -> Result<CubeMap, TextureError> {
+        let (size, _) = dimensions(&faces[0]);
+        for (&face, image) in CubeFace::ALL.iter().zip(faces) {
+            let (width, height) = dimensions(image);
+            if width != size || height != size {
+                return Err(TextureError::CubeFace { face, width, height, size });
+            }
+        }
+        check_size(size, size, gl::MAX_CUBE_MAP_TEXTURE_SIZE)?;
+
+        let texture = Texture::new(gl::TEXTURE_CUBE_MAP);
+        texture.bind();
+        let mut format = None;
+        for (&face, image) in CubeFace::ALL.iter().zip(faces) {
+            let flipped;
+            let image = if self.flip_vertically {
+                flipped = image.flipv();
+                &flipped
+            } else {
+                image
+            };
+            format = Some(upload(face.gl_enum(), image, self.color_space));
+        }
+        let format = format.unwrap();
+        self.apply_sampling(gl::TEXTURE_CUBE_MAP, format);
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, self.wrap_s.gl_enum() as GLint);
+            gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+        }
+        texture.unbind();
+
+        Ok(CubeMap {
+            texture,
+            size,
+            format,
+        })
+    }
+
+    /// Sets the sampling parameters of the texture bound to `target` and
+    /// generates its mipmaps.
+    fn apply_sampling(&self, target: GLenum, format: PixelFormat) {
+        let min_filter = if self.mipmaps { self.min_filter } else { self.min_filter.base_level() };
+
+        unsafe {
+            if let Some(swizzle) = format.swizzle {
+                let swizzle = swizzle.map(|channel| channel as GLint);
+                gl::TexParameteriv(target, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+            }
+
+            gl::TexParameteri(target, gl::TEXTURE_WRAP_S, self.wrap_s.gl_enum() as GLint);
+            gl::TexParameteri(target, gl::TEXTURE_WRAP_T, self.wrap_t.gl_enum() as GLint);
+            gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, min_filter.gl_enum() as GLint);
+            gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, self.mag_filter.gl_enum() as GLint);
+
+            if self.anisotropy > 1.0 && anisotropy_supported() {
+                let mut max = 1.0;
+                gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+                gl::TexParameterf(target, TEXTURE_MAX_ANISOTROPY, self.anisotropy.min(max));
+            }
+
+            if self.mipmaps {
+                gl::GenerateMipmap(target);
+            }
+        }
+    }
+}
+
+fn open_image(path: &Path) -> Result<DynamicImage, TextureError> {
+    image::open(path).map_err(|error| TextureError::Image {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+/// Fails if either side exceeds the limit queried with `max_parameter`.
+fn check_size(width: u32, height: u32, max_parameter: GLenum) -> Result<(), TextureError> {
+    let mut max_size = 0;
+    unsafe {
+        gl::GetIntegerv(max_parameter, &mut max_size);
+    }
+    if width > max_size as u32 || height > max_size as u32 {
+        return Err(TextureError::TooLarge { width, height, max: max_size as u32 });
+    }
+    Ok(())
+}
+
+/// Uploads `image` as level 0 of `target` (a 2D texture or a cube map face)
+/// of the bound texture.
+fn upload(target: GLenum, image: &DynamicImage, color_space: ColorSpace) -> PixelFormat {
+    let (width, height) = dimensions(image);
+    let (format, data) = pixel_data(image, color_space);
+
+    unsafe {
+        let mut alignment = 0;
+        gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment);
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, unpack_alignment(width * format.components));
+
+        gl::TexImage2D(target, 0, format.internal_format as GLint, width as GLsizei,
+                       height as GLsizei, 0, format.format, gl::UNSIGNED_BYTE,
+                       data.as_ptr() as *const c_void);
+
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+    }
+
+    format
+}
+
+fn anisotropy_supported() -> bool {
+    let mut count = 0;
+    unsafe {
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+    }
+
+    (0..count as GLuint).any(|index| {
+        let name = unsafe { gl::GetStringi(gl::EXTENSIONS, index) };
+        !name.is_null() && matches!(unsafe { CStr::from_ptr(name as *const _) }.to_bytes(),
+            b"GL_EXT_texture_filter_anisotropic" | b"GL_ARB_texture_filter_anisotropic")
+    })
+}
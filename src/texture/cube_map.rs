@@ -0,0 +1,291 @@
+use std::f32::consts::PI;
+use std::fmt;
+use std::path::Path;
+
+use cgmath::{InnerSpace, Vector3};
+use image::{DynamicImage, ImageBuffer, Rgba, RgbaImage};
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+use object::Texture;
+
+use super::{dimensions, PixelFormat, TextureBuilder, TextureError, Wrap};
+
+/// The faces of a cube map, in the order OpenGL numbers them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::PositiveX,
+        CubeFace::NegativeX,
+        CubeFace::PositiveY,
+        CubeFace::NegativeY,
+        CubeFace::PositiveZ,
+        CubeFace::NegativeZ,
+    ];
+
+    pub fn gl_enum(self) -> GLenum {
+        match self {
+            CubeFace::PositiveX => gl::TEXTURE_CUBE_MAP_POSITIVE_X,
+            CubeFace::NegativeX => gl::TEXTURE_CUBE_MAP_NEGATIVE_X,
+            CubeFace::PositiveY => gl::TEXTURE_CUBE_MAP_POSITIVE_Y,
+            CubeFace::NegativeY => gl::TEXTURE_CUBE_MAP_NEGATIVE_Y,
+            CubeFace::PositiveZ => gl::TEXTURE_CUBE_MAP_POSITIVE_Z,
+            CubeFace::NegativeZ => gl::TEXTURE_CUBE_MAP_NEGATIVE_Z,
+        }
+    }
+
+    /// The (unnormalized) direction that samples the face at `(u, v)`, where
+    /// both run from 0 to 1 and `v = 0` is the first row of the face image.
+    pub fn direction(self, u: f32, v: f32) -> Vector3<f32> {
+        let s = 2.0 * u - 1.0;
+        let t = 2.0 * v - 1.0;
+        match self {
+            CubeFace::PositiveX => Vector3::new(1.0, -t, -s),
+            CubeFace::NegativeX => Vector3::new(-1.0, -t, s),
+            CubeFace::PositiveY => Vector3::new(s, 1.0, t),
+            CubeFace::NegativeY => Vector3::new(s, -1.0, -t),
+            CubeFace::PositiveZ => Vector3::new(s, -t, 1.0),
+            CubeFace::NegativeZ => Vector3::new(-s, -t, -1.0),
+        }
+    }
+}
+
+impl fmt::Display for CubeFace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            CubeFace::PositiveX => "+X",
+            CubeFace::NegativeX => "-X",
+            CubeFace::PositiveY => "+Y",
+            CubeFace::NegativeY => "-Y",
+            CubeFace::PositiveZ => "+Z",
+            CubeFace::NegativeZ => "-Z",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A cube map texture with six square faces of the same size.
+#[derive(Debug)]
+pub struct CubeMap {
+    pub(super) texture: Texture,
+    pub(super) size: u32,
+    pub(super) format: PixelFormat,
+}
+
+impl CubeMap {
+    /// The `TextureBuilder` defaults with edges clamped, as seams between
+    /// faces would otherwise blend in the opposite edge.
+    pub fn builder() -> TextureBuilder {
+        TextureBuilder::new().wrap(Wrap::ClampToEdge)
+    }
+
+    /// Loads six face images, in `CubeFace::ALL` order.
+    pub fn open_faces<P: AsRef<Path>>(paths: &[P; 6]) -> Result<CubeMap, TextureError> {
+        CubeMap::builder().open_cube_map(paths)
+    }
+
+    /// Loads a single image with the faces laid out as a cross; see
+    /// `cross_faces`.
+    pub fn open_cross<P: AsRef<Path>>(path: P) -> Result<CubeMap, TextureError> {
+        CubeMap::builder().open_cross(path)
+    }
+
+    /// Loads a 360° equirectangular panorama; see `equirectangular_faces`.
+    pub fn open_equirectangular<P: AsRef<Path>>(path: P) -> Result<CubeMap, TextureError> {
+        CubeMap::builder().open_equirectangular(path)
+    }
+
+    /// Width and height of every face.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.texture.id()
+    }
+
+    pub fn bind(&self) {
+        self.texture.bind();
+    }
+
+    /// Makes `unit` the active texture unit and binds the texture to it.
+    pub fn bind_to_unit(&self, unit: u32) {
+        self.texture.bind_to_unit(unit);
+    }
+
+    pub fn unbind(&self) {
+        self.texture.unbind();
+    }
+}
+
+/// Cuts the six faces, in `CubeFace::ALL` order, out of a cross layout.
+///
+/// A 4:3 image is read as a horizontal cross, with `-X +Z +X -Z` in the
+/// middle row and `+Y`/`-Y` above and below `+Z`. A 3:4 image is a vertical
+/// cross with `-Z` upside down below `-Y`.
+pub fn cross_faces(image: &DynamicImage) -> Result<[DynamicImage; 6], TextureError> {
+    let (width, height) = dimensions(image);
+    // (column, row) of each face in CubeFace::ALL order
+    let (size, cells) = if width * 3 == height * 4 && width.is_multiple_of(4) {
+        (width / 4, [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)])
+    } else if width * 4 == height * 3 && width.is_multiple_of(3) {
+        (width / 3, [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (1, 3)])
+    } else {
+        return Err(TextureError::CrossLayout { width, height });
+    };
+
+    let vertical = height > width;
+    let mut faces = cells.iter().map(|&(column, row)| {
+        image.clone().crop(column * size, row * size, size, size)
+    });
+    let mut next = || faces.next().unwrap();
+    let mut faces = [next(), next(), next(), next(), next(), next()];
+    if vertical {
+        faces[5] = faces[5].rotate180();
+    }
+    Ok(faces)
+}
+
+/// Projects a 360°x180° equirectangular panorama onto six `size`x`size`
+/// faces, in `CubeFace::ALL` order. The center of the panorama faces `-Z`,
+/// the default view direction, with `+X` to its right.
+///
+/// The faces are RGBA whatever the layout of `image`.
+pub fn equirectangular_faces(image: &DynamicImage, size: u32) -> [DynamicImage; 6] {
+    let panorama = image.to_rgba();
+    let face = |face: CubeFace| {
+        let pixels = ImageBuffer::from_fn(size, size, |x, y| {
+            let u = (x as f32 + 0.5) / size as f32;
+            let v = (y as f32 + 0.5) / size as f32;
+            let direction = face.direction(u, v).normalize();
+
+            let longitude = direction.x.atan2(-direction.z);
+            let latitude = direction.y.clamp(-1.0, 1.0).acos();
+            sample_bilinear(&panorama, 0.5 + longitude / (2.0 * PI), latitude / PI)
+        });
+        DynamicImage::ImageRgba8(pixels)
+    };
+
+    [
+        face(CubeFace::PositiveX),
+        face(CubeFace::NegativeX),
+        face(CubeFace::PositiveY),
+        face(CubeFace::NegativeY),
+        face(CubeFace::PositiveZ),
+        face(CubeFace::NegativeZ),
+    ]
+}
+
+// Wraps around horizontally and clamps at the poles
+fn sample_bilinear(image: &RgbaImage, u: f32, v: f32) -> Rgba<u8> {
+    let (width, height) = image.dimensions();
+    let x = u * width as f32 - 0.5;
+    let y = (v * height as f32 - 0.5).clamp(0.0, height as f32 - 1.0);
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = x - x0;
+    let fy = y - y0;
+
+    let column = |x: f32| (x as i64).rem_euclid(width as i64) as u32;
+    let row = |y: f32| (y as u32).min(height - 1);
+    let (left, right) = (column(x0), column(x0 + 1.0));
+    let (top, bottom) = (row(y0), row(y0 + 1.0));
+
+    let mut data = [0; 4];
+    for (channel, value) in data.iter_mut().enumerate() {
+        let texel = |x, y| image.get_pixel(x, y).data[channel] as f32;
+        let upper = texel(left, top) * (1.0 - fx) + texel(right, top) * fx;
+        let lower = texel(left, bottom) * (1.0 - fx) + texel(right, bottom) * fx;
+        *value = (upper * (1.0 - fy) + lower * fy).round() as u8;
+    }
+    Rgba { data }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::{GenericImage, RgbImage, Rgb};
+
+    #[test]
+    fn face_directions_point_outwards() {
+        for &face in &CubeFace::ALL {
+            let center = face.direction(0.5, 0.5);
+            let axis = match face {
+                CubeFace::PositiveX => Vector3::unit_x(),
+                CubeFace::NegativeX => -Vector3::unit_x(),
+                CubeFace::PositiveY => Vector3::unit_y(),
+                CubeFace::NegativeY => -Vector3::unit_y(),
+                CubeFace::PositiveZ => Vector3::unit_z(),
+                CubeFace::NegativeZ => -Vector3::unit_z(),
+            };
+            assert_eq!(center, axis, "{}", face);
+        }
+    }
+
+    #[test]
+    fn horizontal_cross_edges_meet() {
+        // The right edge of +Z is the left edge of +X, its top edge the bottom of +Y
+        assert_eq!(CubeFace::PositiveZ.direction(1.0, 0.3), CubeFace::PositiveX.direction(0.0, 0.3));
+        assert_eq!(CubeFace::PositiveX.direction(1.0, 0.3), CubeFace::NegativeZ.direction(0.0, 0.3));
+        assert_eq!(CubeFace::PositiveZ.direction(0.3, 0.0), CubeFace::PositiveY.direction(0.3, 1.0));
+        assert_eq!(CubeFace::PositiveZ.direction(0.3, 1.0), CubeFace::NegativeY.direction(0.3, 0.0));
+    }
+
+    fn cross(width: u32, height: u32, size: u32) -> DynamicImage {
+        // Each cell is filled with (column, row, 0)
+        let pixels = RgbImage::from_fn(width, height, |x, y| Rgb { data: [(x / size) as u8, (y / size) as u8, 0] });
+        DynamicImage::ImageRgb8(pixels)
+    }
+
+    #[test]
+    fn cross_layouts_are_cut_into_faces() {
+        let faces = cross_faces(&cross(8, 6, 2)).unwrap();
+        let cells: Vec<[u8; 3]> = faces.iter().map(|face| face.to_rgb().get_pixel(0, 0).data).collect();
+        assert_eq!(cells, vec![[2, 1, 0], [0, 1, 0], [1, 0, 0], [1, 2, 0], [1, 1, 0], [3, 1, 0]]);
+
+        let faces = cross_faces(&cross(6, 8, 2)).unwrap();
+        assert_eq!(faces[5].to_rgb().get_pixel(0, 0).data, [1, 3, 0]);
+        assert_eq!(faces[5].dimensions(), (2, 2));
+    }
+
+    #[test]
+    fn other_shapes_are_not_crosses() {
+        match cross_faces(&cross(8, 8, 2)) {
+            Err(TextureError::CrossLayout { width: 8, height: 8 }) => {},
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn panorama_center_faces_forward() {
+        // Left half red, right half blue: -Z sits on the seam, +X in the blue half
+        let pixels = RgbImage::from_fn(16, 8, |x, _| if x < 8 { Rgb { data: [255, 0, 0] } } else { Rgb { data: [0, 0, 255] } });
+        let faces = equirectangular_faces(&DynamicImage::ImageRgb8(pixels), 4);
+
+        assert_eq!(faces[0].to_rgba().get_pixel(2, 2).data, [0, 0, 255, 255]);
+        assert_eq!(faces[1].to_rgba().get_pixel(2, 2).data, [255, 0, 0, 255]);
+
+        let forward = faces[5].to_rgba();
+        assert_eq!(forward.get_pixel(0, 2).data, [0, 0, 255, 255]);
+        assert_eq!(forward.get_pixel(3, 2).data, [255, 0, 0, 255]);
+    }
+}
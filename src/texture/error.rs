@@ -0,0 +1,45 @@
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+use image::ImageError;
+
+use super::CubeFace;
+
+#[derive(Debug)]
+pub enum TextureError {
+    /// The image file could not be read or decoded.
+    Image { path: PathBuf, error: ImageError },
+    /// The image exceeds `GL_MAX_TEXTURE_SIZE`.
+    TooLarge { width: u32, height: u32, max: u32 },
+    /// A cube map face that is not square or differs in size from the first.
+    CubeFace { face: CubeFace, width: u32, height: u32, size: u32 },
+    /// An image that is neither a 4:3 nor a 3:4 cross of square faces.
+    CrossLayout { width: u32, height: u32 },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TextureError::Image { ref path, ref error } => write!(f, "{}: {}", path.display(), error),
+            TextureError::TooLarge { width, height, max } => {
+                write!(f, "a {}x{} image exceeds the maximum texture size of {}", width, height, max)
+            },
+            TextureError::CubeFace { face, width, height, size } => {
+                write!(f, "cube map face {} is {}x{}, expected {}x{}", face, width, height, size, size)
+            },
+            TextureError::CrossLayout { width, height } => {
+                write!(f, "a {}x{} image is not a 4:3 or 3:4 cube map cross", width, height)
+            },
+        }
+    }
+}
+
+impl Error for TextureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            TextureError::Image { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
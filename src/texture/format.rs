@@ -0,0 +1,120 @@
+use std::borrow::Cow;
+
+use image::DynamicImage;
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+
+use super::ColorSpace;
+
+/// How the pixels of an image are uploaded and sampled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PixelFormat {
+    pub internal_format: GLenum,
+    /// The layout of the uploaded data, e.g. `gl::RG`.
+    pub format: GLenum,
+    pub components: u32,
+    /// Maps one- and two-channel textures to `vec4(grey, grey, grey, alpha)`
+    /// in shaders.
+    pub swizzle: Option<[GLenum; 4]>,
+}
+
+impl PixelFormat {
+    const GREY: PixelFormat = PixelFormat {
+        internal_format: gl::R8,
+        format: gl::RED,
+        components: 1,
+        swizzle: Some([gl::RED, gl::RED, gl::RED, gl::ONE]),
+    };
+
+    const GREY_ALPHA: PixelFormat = PixelFormat {
+        internal_format: gl::RG8,
+        format: gl::RG,
+        components: 2,
+        swizzle: Some([gl::RED, gl::RED, gl::RED, gl::GREEN]),
+    };
+
+    fn rgb(color_space: ColorSpace) -> PixelFormat {
+        PixelFormat {
+            internal_format: match color_space {
+                ColorSpace::Linear => gl::RGB8,
+                ColorSpace::Srgb => gl::SRGB8,
+            },
+            format: gl::RGB,
+            components: 3,
+            swizzle: None,
+        }
+    }
+
+    fn rgba(color_space: ColorSpace) -> PixelFormat {
+        PixelFormat {
+            internal_format: match color_space {
+                ColorSpace::Linear => gl::RGBA8,
+                ColorSpace::Srgb => gl::SRGB8_ALPHA8,
+            },
+            format: gl::RGBA,
+            components: 4,
+            swizzle: None,
+        }
+    }
+}
+
+/// Picks the format for `image` and returns its pixels in that format.
+///
+/// There are no one- and two-channel sRGB formats in core OpenGL, so grey
+/// sRGB images are expanded to RGB(A).
+pub fn pixel_data(image: &DynamicImage, color_space: ColorSpace) -> (PixelFormat, Cow<'_, [u8]>) {
+    match (image, color_space) {
+        (DynamicImage::ImageLuma8(grey), ColorSpace::Linear) => (PixelFormat::GREY, Cow::Borrowed(&**grey)),
+        (DynamicImage::ImageLumaA8(grey), ColorSpace::Linear) => (PixelFormat::GREY_ALPHA, Cow::Borrowed(&**grey)),
+        (DynamicImage::ImageLuma8(_), ColorSpace::Srgb) => (PixelFormat::rgb(color_space), Cow::Owned(image.to_rgb().into_raw())),
+        (DynamicImage::ImageLumaA8(_), ColorSpace::Srgb) => (PixelFormat::rgba(color_space), Cow::Owned(image.to_rgba().into_raw())),
+        (DynamicImage::ImageRgb8(rgb), _) => (PixelFormat::rgb(color_space), Cow::Borrowed(&**rgb)),
+        (DynamicImage::ImageRgba8(rgba), _) => (PixelFormat::rgba(color_space), Cow::Borrowed(&**rgba)),
+    }
+}
+
+/// The largest `GL_UNPACK_ALIGNMENT` that rows of `row_bytes` bytes satisfy.
+/// The default of 4 skews RGB and grey images with odd widths.
+pub fn unpack_alignment(row_bytes: u32) -> GLint {
+    // The lowest set bit is the largest power of two dividing the size
+    (row_bytes & row_bytes.wrapping_neg()).clamp(1, 8) as GLint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::{GrayAlphaImage, GrayImage, RgbImage};
+
+    #[test]
+    fn formats_follow_the_channels() {
+        let grey = DynamicImage::ImageLuma8(GrayImage::new(3, 1));
+        let (format, data) = pixel_data(&grey, ColorSpace::Linear);
+        assert_eq!(format, PixelFormat::GREY);
+        assert_eq!(data.len(), 3);
+
+        let grey_alpha = DynamicImage::ImageLumaA8(GrayAlphaImage::new(3, 1));
+        assert_eq!(pixel_data(&grey_alpha, ColorSpace::Linear).0.internal_format, gl::RG8);
+
+        let rgb = DynamicImage::ImageRgb8(RgbImage::new(3, 1));
+        assert_eq!(pixel_data(&rgb, ColorSpace::Linear).0.internal_format, gl::RGB8);
+        assert_eq!(pixel_data(&rgb, ColorSpace::Srgb).0.internal_format, gl::SRGB8);
+    }
+
+    #[test]
+    fn srgb_grey_is_expanded() {
+        let grey_alpha = DynamicImage::ImageLumaA8(GrayAlphaImage::new(3, 1));
+        let (format, data) = pixel_data(&grey_alpha, ColorSpace::Srgb);
+        assert_eq!(format.internal_format, gl::SRGB8_ALPHA8);
+        assert_eq!(data.len(), 3 * 4);
+    }
+
+    #[test]
+    fn alignment_matches_the_row_size() {
+        assert_eq!(unpack_alignment(3 * 3), 1);
+        assert_eq!(unpack_alignment(3 * 2), 2);
+        assert_eq!(unpack_alignment(4 * 3), 4);
+        assert_eq!(unpack_alignment(4 * 4), 8);
+    }
+}
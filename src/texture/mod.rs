@@ -0,0 +1,155 @@
+use std::path::Path;
+
+use image::DynamicImage;
+
+use gl_gen::gl;
+use gl_gen::gl::types::*;
+use object::Texture;
+
+mod builder;
+mod cube_map;
+mod error;
+mod format;
+pub use self::builder::TextureBuilder;
+pub use self::cube_map::{cross_faces, equirectangular_faces, CubeFace, CubeMap};
+pub use self::error::TextureError;
+pub use self::format::{pixel_data, unpack_alignment, PixelFormat};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+impl Wrap {
+    pub fn gl_enum(self) -> GLenum {
+        match self {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Wrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Wrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// Magnification filter, and the texel part of `MinFilter`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+impl Filter {
+    pub fn gl_enum(self) -> GLenum {
+        match self {
+            Filter::Nearest => gl::NEAREST,
+            Filter::Linear => gl::LINEAR,
+        }
+    }
+}
+
+/// Minification filter: texel filter, then how mipmap levels are picked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MinFilter {
+    Nearest,
+    Linear,
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+impl MinFilter {
+    pub fn gl_enum(self) -> GLenum {
+        match self {
+            MinFilter::Nearest => gl::NEAREST,
+            MinFilter::Linear => gl::LINEAR,
+            MinFilter::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+            MinFilter::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+            MinFilter::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+            MinFilter::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    /// The same texel filter without mipmapping.
+    pub fn base_level(self) -> MinFilter {
+        match self {
+            MinFilter::Nearest | MinFilter::NearestMipmapNearest | MinFilter::NearestMipmapLinear => MinFilter::Nearest,
+            MinFilter::Linear | MinFilter::LinearMipmapNearest | MinFilter::LinearMipmapLinear => MinFilter::Linear,
+        }
+    }
+}
+
+/// How stored color values are interpreted when sampled.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Values are used as stored.
+    Linear,
+    /// Values are decoded from sRGB to linear by the sampler.
+    Srgb,
+}
+
+/// A 2D texture uploaded from an image. Create it with `Texture2D::open` or
+/// `Texture2D::builder`.
+#[derive(Debug)]
+pub struct Texture2D {
+    texture: Texture,
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+}
+
+impl Texture2D {
+    pub fn builder() -> TextureBuilder {
+        TextureBuilder::new()
+    }
+
+    /// Loads the image at `path` with the `TextureBuilder` defaults.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Texture2D, TextureError> {
+        TextureBuilder::new().open(path)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn id(&self) -> GLuint {
+        self.texture.id()
+    }
+
+    pub fn bind(&self) {
+        self.texture.bind();
+    }
+
+    /// Makes `unit` the active texture unit and binds the texture to it.
+    pub fn bind_to_unit(&self, unit: u32) {
+        self.texture.bind_to_unit(unit);
+    }
+
+    pub fn unbind(&self) {
+        self.texture.unbind();
+    }
+}
+
+fn dimensions(image: &DynamicImage) -> (u32, u32) {
+    match *image {
+        DynamicImage::ImageLuma8(ref image) => image.dimensions(),
+        DynamicImage::ImageLumaA8(ref image) => image.dimensions(),
+        DynamicImage::ImageRgb8(ref image) => image.dimensions(),
+        DynamicImage::ImageRgba8(ref image) => image.dimensions(),
+    }
+}
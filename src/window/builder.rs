@@ -0,0 +1,130 @@
+use sdl2;
+use sdl2::video::GLProfile;
+
+use context::ContextId;
+use gl_gen::gl;
+
+use super::{Window, WindowError};
+
+/// Configures the window and its OpenGL context before creating both.
+///
+/// ```no_run
+/// # extern crate sdl2;
+/// # extern crate opengl_sdl2_demo;
+/// # use opengl_sdl2_demo::window::Window;
+/// # use sdl2::video::GLProfile;
+/// # fn main() {
+/// let window = Window::builder("Cubes")
+///     .size(1280, 720)
+///     .gl_version(4, 1)
+///     .gl_profile(GLProfile::Core)
+///     .msaa(4)
+///     .build()
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct WindowBuilder {
+    title: String,
+    width: u32,
+    height: u32,
+    gl_version: (u8, u8),
+    gl_profile: GLProfile,
+    vsync: bool,
+    msaa_samples: u8,
+    resizable: bool,
+}
+
+impl WindowBuilder {
+    /// An 800x600 resizable window with a vsynced OpenGL 3.3 core context.
+    pub fn new(title: &str) -> WindowBuilder {
+        WindowBuilder {
+            title: title.to_string(),
+            width: super::DEFAULT_WIDTH,
+            height: super::DEFAULT_HEIGHT,
+            gl_version: (3, 3),
+            gl_profile: GLProfile::Core,
+            vsync: true,
+            msaa_samples: 0,
+            resizable: true,
+        }
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> WindowBuilder {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn gl_version(mut self, major: u8, minor: u8) -> WindowBuilder {
+        self.gl_version = (major, minor);
+        self
+    }
+
+    pub fn gl_profile(mut self, profile: GLProfile) -> WindowBuilder {
+        self.gl_profile = profile;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> WindowBuilder {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Number of samples per pixel of the default framebuffer; 0 disables MSAA.
+    pub fn msaa(mut self, samples: u8) -> WindowBuilder {
+        self.msaa_samples = samples;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> WindowBuilder {
+        self.resizable = resizable;
+        self
+    }
+
+    /// Creates the window, makes its context current and loads the GL functions.
+    pub fn build(self) -> Result<Window, WindowError> {
+        let sdl_context = sdl2::init().map_err(WindowError::Sdl)?;
+        let video_subsystem = sdl_context.video().map_err(WindowError::Sdl)?;
+
+        {
+            let gl_attr = video_subsystem.gl_attr();
+            gl_attr.set_context_profile(self.gl_profile);
+            gl_attr.set_context_version(self.gl_version.0, self.gl_version.1);
+            gl_attr.set_double_buffer(true);
+            gl_attr.set_depth_size(24);
+            if self.msaa_samples > 0 {
+                gl_attr.set_multisample_buffers(1);
+                gl_attr.set_multisample_samples(self.msaa_samples);
+            }
+        }
+
+        let mut window_builder = video_subsystem.window(&self.title, self.width, self.height);
+        window_builder.position_centered().opengl();
+        if self.resizable {
+            window_builder.resizable();
+        }
+        let window = window_builder.build()?;
+
+        let gl_context = window.gl_create_context().map_err(WindowError::Context)?;
+        window.gl_make_current(&gl_context).map_err(WindowError::Context)?;
+        gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
+
+        // Not every driver lets us pick the swap interval; keep going either way
+        video_subsystem.gl_set_swap_interval(if self.vsync { 1 } else { 0 });
+
+        if self.msaa_samples > 0 {
+            unsafe {
+                gl::Enable(gl::MULTISAMPLE);
+            }
+        }
+
+        Ok(Window {
+            sdl_context,
+            video_subsystem,
+            gl_context,
+            window,
+            context_id: ContextId::register(),
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use std::error::Error;
+use std::fmt;
+
+use sdl2::video::WindowBuildError;
+
+#[derive(Debug)]
+pub enum WindowError {
+    /// SDL itself or one of its subsystems failed to initialize.
+    Sdl(String),
+    Build(WindowBuildError),
+    /// The requested OpenGL context could not be created or made current.
+    Context(String),
+}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WindowError::Sdl(ref error) => write!(f, "failed to initialize SDL: {}", error),
+            WindowError::Build(ref error) => write!(f, "failed to create the window: {}", error),
+            WindowError::Context(ref error) => write!(f, "failed to set up the OpenGL context: {}", error),
+        }
+    }
+}
+
+impl Error for WindowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            WindowError::Build(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<WindowBuildError> for WindowError {
+    fn from(error: WindowBuildError) -> WindowError {
+        WindowError::Build(error)
+    }
+}
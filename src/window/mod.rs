@@ -0,0 +1,135 @@
+use std::io;
+use std::path::Path;
+
+use image::RgbImage;
+use sdl2::{EventPump, Sdl, TimerSubsystem, VideoSubsystem};
+use sdl2::video::{self, GLContext};
+
+use capture;
+use context::{ContextId, Surface};
+
+mod builder;
+mod error;
+pub use self::builder::WindowBuilder;
+pub use self::error::WindowError;
+
+const DEFAULT_WIDTH: u32 = 800;
+const DEFAULT_HEIGHT: u32 = 600;
+
+/// SDL window with a current OpenGL context and loaded GL function pointers.
+pub struct Window {
+    sdl_context: Sdl,
+    video_subsystem: VideoSubsystem,
+    // Declared before `window` so the context is destroyed first
+    gl_context: GLContext,
+    window: video::Window,
+    context_id: ContextId,
+}
+
+impl Window {
+    pub fn builder(title: &str) -> WindowBuilder {
+        WindowBuilder::new(title)
+    }
+
+    /// An 800x600 window with the builder defaults. Panics if it cannot be created.
+    pub fn new(title: &str) -> Window {
+        Self::with_size(title, DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+
+    pub fn with_size(title: &str, width: u32, height: u32) -> Window {
+        Self::builder(title)
+            .size(width, height)
+            .build()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn sdl(&self) -> &Sdl {
+        &self.sdl_context
+    }
+
+    pub fn video(&self) -> &VideoSubsystem {
+        &self.video_subsystem
+    }
+
+    pub fn window(&self) -> &video::Window {
+        &self.window
+    }
+
+    pub fn gl_context(&self) -> &GLContext {
+        &self.gl_context
+    }
+
+    pub fn event_pump(&self) -> EventPump {
+        self.sdl_context.event_pump().unwrap()
+    }
+
+    pub fn timer(&self) -> TimerSubsystem {
+        self.sdl_context.timer().unwrap()
+    }
+
+    pub fn context_id(&self) -> ContextId {
+        self.context_id
+    }
+
+    /// Size of the window in screen coordinates.
+    pub fn size(&self) -> (u32, u32) {
+        self.window.size()
+    }
+
+    /// Size of the framebuffer in pixels, which differs from `size` on high-DPI displays.
+    pub fn drawable_size(&self) -> (u32, u32) {
+        self.window.drawable_size()
+    }
+
+    /// Reads back the default framebuffer; call before `swap`.
+    pub fn screenshot(&self) -> RgbImage {
+        let (width, height) = self.drawable_size();
+        capture::capture(0, width, height)
+    }
+
+    /// Writes `screenshot` to `path` as PNG or JPEG, depending on the extension.
+    pub fn save_screenshot<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.screenshot().save(path)
+    }
+
+    /// Captures the mouse for mouse-look: hides the cursor, keeps it in the
+    /// window and reports relative motion (`xrel`/`yrel`) even at the edges of
+    /// the screen. `false` releases it again.
+    pub fn set_mouse_captured(&self, captured: bool) {
+        self.sdl_context.mouse().set_relative_mouse_mode(captured);
+    }
+
+    pub fn is_mouse_captured(&self) -> bool {
+        self.sdl_context.mouse().relative_mouse_mode()
+    }
+
+    /// Makes the window's GL context current on this thread, e.g. after
+    /// rendering with another context. Deletions of GL objects dropped while
+    /// another context was current happen now.
+    pub fn make_current(&self) -> Result<(), WindowError> {
+        self.window.gl_make_current(&self.gl_context).map_err(WindowError::Context)?;
+        self.context_id.make_current();
+        Ok(())
+    }
+
+    pub fn swap(&self) {
+        self.window.gl_swap_window();
+    }
+}
+
+impl Surface for Window {
+    fn context_id(&self) -> ContextId {
+        self.context_id
+    }
+
+    fn drawable_size(&self) -> (u32, u32) {
+        self.window.drawable_size()
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        // Runs before the GL context is dropped
+        self.context_id.unregister();
+    }
+}
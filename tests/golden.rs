@@ -0,0 +1,134 @@
+//! Renders every example offscreen for a fixed simulated time and compares
+//! the frame with the reference image in tests/reference.
+//!
+//! Run with `UPDATE_GOLDEN=1` to (re)write the references. On a mismatch the
+//! rendered frame and a diff image (mismatched pixels in red) are written to
+//! target/golden-diff.
+//!
+//! The tests fail where no headless context can be created (no EGL), so a
+//! run that compared nothing never shows up as a pass.
+#![cfg(target_os = "linux")]
+
+extern crate cgmath;
+extern crate image;
+extern crate opengl_sdl2_demo;
+extern crate sdl2;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use image::{Rgb, RgbImage};
+
+use opengl_sdl2_demo::app::{self, App};
+use opengl_sdl2_demo::headless::HeadlessContext;
+
+macro_rules! examples {
+    ($($module:ident: $path:expr, $app:ident;)*) => {
+        $(
+            #[path = $path]
+            #[allow(dead_code)]
+            mod $module;
+
+            #[test]
+            fn $module() {
+                check::<$module::$app>(stringify!($module));
+            }
+        )*
+    };
+}
+
+examples! {
+    rectangle: "../src/examples/hello_triangle/rectangle.rs", Rectangle;
+    two_triangles: "../src/examples/hello_triangle/two_triangles.rs", TwoTriangles;
+    two_triangles_and_vbo: "../src/examples/hello_triangle/two_triangles_and_vbo.rs", TwoTrianglesAndVbo;
+    two_triangles_with_shaders: "../src/examples/hello_triangle/two_triangles_with_shaders.rs", TwoTrianglesWithShaders;
+    uniform: "../src/examples/shaders/uniform.rs", Uniform;
+    triangle_vertex_colors: "../src/examples/shaders/triangle_vertex_colors.rs", TriangleVertexColors;
+    triangle_exercise_3: "../src/examples/shaders/triangle_exercise_3.rs", TriangleExercise3;
+    rectangle_texture: "../src/examples/textures/rectangle_texture.rs", RectangleTexture;
+    transformation: "../src/examples/transformations/transformation.rs", Transformation;
+    model_view_matrix: "../src/examples/coordinate_systems/model_view_matrix.rs", ModelViewMatrix;
+    mvm_more_cubes: "../src/examples/coordinate_systems/mvm_more_cubes.rs", MvmMoreCubes;
+    camera_view: "../src/examples/camera/camera_view.rs", CameraView;
+    camera_class: "../src/examples/camera/camera_class.rs", CameraClass;
+    model_loading: "../src/examples/model_loading/model_loading.rs", ModelLoading;
+    scene_viewer: "../src/examples/model_loading/scene_viewer.rs", SceneViewer;
+}
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 120;
+/// One simulated second at 60 fps.
+const FRAMES: u32 = 60;
+const DT: f32 = 1.0 / 60.0;
+
+/// Largest per-channel difference still counted as a match.
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Share of pixels allowed to exceed `CHANNEL_TOLERANCE`, to absorb
+/// rasterization differences between Mesa versions along edges.
+const MAX_MISMATCHED: f64 = 0.005;
+
+// gl::load_with writes process wide function pointers, so render one example at a time
+static RENDER: Mutex<()> = Mutex::new(());
+
+fn check<A: App>(name: &str) {
+    let actual = {
+        let _guard = RENDER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // A regression gate that cannot run must not pass
+        let context = HeadlessContext::new(WIDTH, HEIGHT)
+            .unwrap_or_else(|err| panic!("{}: no headless GL context ({}); golden tests need Mesa's EGL", name, err));
+        app::render_headless::<A>(&context, FRAMES, DT)
+    };
+
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let reference_path = root.join("tests/reference").join(format!("{}.png", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+        actual.save(&reference_path).unwrap();
+        return;
+    }
+
+    let expected = match image::open(&reference_path) {
+        Ok(image) => image.to_rgb(),
+        Err(err) => panic!("cannot read {} ({}); run with UPDATE_GOLDEN=1 to create it", reference_path.display(), err),
+    };
+    assert_eq!(expected.dimensions(), actual.dimensions(), "{}: reference has a different size", name);
+
+    let (diff, mismatched) = diff(&expected, &actual);
+    let allowed = (f64::from(WIDTH * HEIGHT) * MAX_MISMATCHED) as u32;
+    if mismatched > allowed {
+        let out = root.join("target/golden-diff");
+        fs::create_dir_all(&out).unwrap();
+        actual.save(out.join(format!("{}-actual.png", name))).unwrap();
+        diff.save(out.join(format!("{}-diff.png", name))).unwrap();
+
+        panic!("{}: {} pixels differ from {} (at most {} allowed); see {}",
+               name, mismatched, reference_path.display(), allowed, out.display());
+    }
+}
+
+/// Marks pixels off by more than `CHANNEL_TOLERANCE` in red over a dimmed
+/// copy of the reference and counts them.
+fn diff(expected: &RgbImage, actual: &RgbImage) -> (RgbImage, u32) {
+    let mut diff = RgbImage::new(expected.width(), expected.height());
+    let mut mismatched = 0;
+
+    for (x, y, pixel) in expected.enumerate_pixels() {
+        let e = pixel.data;
+        let a = actual.get_pixel(x, y).data;
+
+        let off = e.iter().zip(a.iter()).any(|(&e, &a)| (i16::from(e) - i16::from(a)).abs() > i16::from(CHANNEL_TOLERANCE));
+        let marked = if off {
+            mismatched += 1;
+            [255, 0, 0]
+        } else {
+            let luma = ((u16::from(e[0]) + u16::from(e[1]) + u16::from(e[2])) / 3 / 4) as u8;
+            [luma, luma, luma]
+        };
+        diff.put_pixel(x, y, Rgb { data: marked });
+    }
+
+    (diff, mismatched)
+}